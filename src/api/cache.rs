@@ -0,0 +1,275 @@
+//! On-disk cache of check and enhance results
+//!
+//! Entries live in `~/.cache/akkurate/results`, one JSON file per request,
+//! named after a hash of everything that affects the answer. The full key is
+//! stored in the entry as well, so a hash collision is a miss, not a wrong
+//! result.
+
+use crate::config::settings::{cache_dir, write_private, CacheConfig, SafetyThreshold};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+/// Everything a cached result depends on
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CacheKey {
+    /// `check` or `enhance`
+    pub operation: String,
+    pub model: String,
+    /// Base URL of the models API, as a gateway may answer differently
+    #[serde(default)]
+    pub endpoint: String,
+    #[serde(default)]
+    pub safety_threshold: SafetyThreshold,
+    /// Hash of the instructions, which cover the UI language, the preset,
+    /// the dictionary and custom prompt templates
+    pub instructions: String,
+    pub text: String,
+}
+
+impl CacheKey {
+    pub fn new(operation: &str, model: &str, instructions: &str, text: &str) -> Self {
+        Self {
+            operation: operation.to_string(),
+            model: model.to_string(),
+            endpoint: String::new(),
+            safety_threshold: SafetyThreshold::Default,
+            instructions: format!("{:016x}", fnv1a(instructions.as_bytes())),
+            text: text.to_string(),
+        }
+    }
+
+    /// Set the request settings besides the prompt that change the answer
+    pub fn with_request_settings(
+        mut self,
+        endpoint: &str,
+        safety_threshold: SafetyThreshold,
+    ) -> Self {
+        self.endpoint = endpoint.to_string();
+        self.safety_threshold = safety_threshold;
+        self
+    }
+
+    /// Name of the file holding this entry
+    pub(crate) fn file_name(&self) -> String {
+        let safety = self.safety_threshold.api_name().unwrap_or_default();
+        let mut bytes = Vec::new();
+        for part in [
+            &self.operation,
+            &self.model,
+            &self.endpoint,
+            safety,
+            &self.instructions,
+            &self.text,
+        ] {
+            bytes.extend_from_slice(part.as_bytes());
+            // Separator that can't appear in UTF-8 text
+            bytes.push(0xff);
+        }
+        format!("{:016x}.json", fnv1a(&bytes))
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct Entry<T> {
+    key: CacheKey,
+    value: T,
+}
+
+/// Result cache with an age and a size limit
+#[derive(Debug, Clone)]
+pub struct ResultCache {
+    dir: PathBuf,
+    ttl: Duration,
+    max_bytes: u64,
+}
+
+impl ResultCache {
+    pub fn new(dir: PathBuf, ttl: Duration, max_bytes: u64) -> Self {
+        Self {
+            dir,
+            ttl,
+            max_bytes,
+        }
+    }
+
+    /// Create the cache described by the config, or `None` if it is turned off
+    pub fn from_config(config: &CacheConfig) -> Option<Self> {
+        if !config.enabled {
+            return None;
+        }
+        Some(Self::new(
+            results_dir()?,
+            Duration::from_secs(config.ttl_hours.saturating_mul(3600)),
+            config.max_size_mb.saturating_mul(1024 * 1024),
+        ))
+    }
+
+    /// Look up a result that is younger than the TTL
+    pub fn get<T: DeserializeOwned>(&self, key: &CacheKey) -> Option<T> {
+        let path = self.dir.join(key.file_name());
+        let age = std::fs::metadata(&path)
+            .and_then(|meta| meta.modified())
+            .ok()
+            .and_then(|modified| modified.elapsed().ok())?;
+        if age > self.ttl {
+            return None;
+        }
+
+        let content = std::fs::read_to_string(&path).ok()?;
+        match serde_json::from_str::<Entry<T>>(&content) {
+            Ok(entry) if entry.key == *key => Some(entry.value),
+            Ok(_) => None,
+            Err(e) => {
+                tracing::debug!("Ignoring unreadable cache entry {}: {}", path.display(), e);
+                None
+            }
+        }
+    }
+
+    /// Store a result, then drop entries over the age and size limits
+    ///
+    /// Failures are logged; a broken cache never fails a request.
+    pub fn put<T: Serialize>(&self, key: &CacheKey, value: &T) {
+        let entry = Entry {
+            key: key.clone(),
+            value,
+        };
+        let result = std::fs::create_dir_all(&self.dir)
+            .map_err(anyhow::Error::from)
+            .and_then(|()| Ok(serde_json::to_string(&entry)?))
+            .and_then(|json| Ok(write_private(&self.dir.join(key.file_name()), &json)?));
+        if let Err(e) = result {
+            tracing::warn!("Failed to write cache entry: {:#}", e);
+            return;
+        }
+        self.prune();
+    }
+
+    /// Remove expired entries, then the oldest ones until under the size limit
+    fn prune(&self) {
+        let mut entries = entries(&self.dir);
+        let now = SystemTime::now();
+        entries.retain(|(path, modified, _)| {
+            let expired = now
+                .duration_since(*modified)
+                .is_ok_and(|age| age > self.ttl);
+            if expired {
+                let _ = std::fs::remove_file(path);
+            }
+            !expired
+        });
+
+        let mut total: u64 = entries.iter().map(|(_, _, size)| size).sum();
+        entries.sort_by_key(|(_, modified, _)| *modified);
+        for (path, _, size) in entries {
+            if total <= self.max_bytes {
+                break;
+            }
+            if std::fs::remove_file(&path).is_ok() {
+                total -= size;
+            }
+        }
+    }
+}
+
+/// Directory holding cached results
+pub fn results_dir() -> Option<PathBuf> {
+    cache_dir().map(|dir| dir.join("results"))
+}
+
+/// Remove every cached result
+pub fn clear_cache() -> std::io::Result<()> {
+    let Some(dir) = results_dir() else {
+        return Ok(());
+    };
+    match std::fs::remove_dir_all(dir) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+/// Path, modification time and size of each entry in `dir`
+fn entries(dir: &Path) -> Vec<(PathBuf, SystemTime, u64)> {
+    let Ok(read_dir) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    read_dir
+        .flatten()
+        .filter(|entry| {
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            !name.starts_with('.') && name.ends_with(".json")
+        })
+        .filter_map(|entry| {
+            let meta = entry.metadata().ok()?;
+            Some((entry.path(), meta.modified().ok()?, meta.len()))
+        })
+        .collect()
+}
+
+/// 64-bit FNV-1a, which unlike `DefaultHasher` is stable across Rust releases
+pub(crate) fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x100000001b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_cache(name: &str, max_bytes: u64) -> ResultCache {
+        let dir =
+            std::env::temp_dir().join(format!("akkurate-cache-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        ResultCache::new(dir, Duration::from_secs(3600), max_bytes)
+    }
+
+    #[test]
+    fn stored_results_are_returned_for_the_same_key_only() {
+        let cache = temp_cache("roundtrip", 1024 * 1024);
+        let key = CacheKey::new("check", "model", "instructions", "I has a apple");
+        cache.put(&key, &"I have an apple".to_string());
+
+        assert_eq!(
+            cache.get::<String>(&key).as_deref(),
+            Some("I have an apple")
+        );
+        for other in [
+            CacheKey::new("enhance", "model", "instructions", "I has a apple"),
+            CacheKey::new("check", "other-model", "instructions", "I has a apple"),
+            CacheKey::new("check", "model", "other instructions", "I has a apple"),
+            CacheKey::new("check", "model", "instructions", "I has an apple"),
+            key.clone()
+                .with_request_settings("https://gateway.example", SafetyThreshold::Default),
+            key.clone()
+                .with_request_settings("", SafetyThreshold::BlockNone),
+        ] {
+            assert_eq!(cache.get::<String>(&other), None);
+        }
+
+        let _ = std::fs::remove_dir_all(&cache.dir);
+    }
+
+    #[test]
+    fn oldest_entries_are_dropped_over_the_size_limit() {
+        let cache = temp_cache("prune", 250);
+        let keys: Vec<_> = (0..4)
+            .map(|i| CacheKey::new("check", "model", "", &format!("text {}", i)))
+            .collect();
+        for key in &keys {
+            cache.put(key, &"x".repeat(40));
+            // Distinct modification times
+            std::thread::sleep(Duration::from_millis(20));
+        }
+
+        assert_eq!(cache.get::<String>(&keys[0]), None);
+        assert!(cache.get::<String>(&keys[3]).is_some());
+        let total: u64 = entries(&cache.dir).iter().map(|(_, _, size)| size).sum();
+        assert!(total <= 250);
+
+        let _ = std::fs::remove_dir_all(&cache.dir);
+    }
+}
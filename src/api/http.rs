@@ -0,0 +1,111 @@
+//! HTTP client setup: proxy, private certificate authorities and client certificates
+
+use crate::config::secrets;
+use crate::config::settings::NetworkConfig;
+use anyhow::{bail, Context, Result};
+use std::path::PathBuf;
+
+/// Build the HTTP client described by the network settings
+pub fn build_client(config: &NetworkConfig) -> Result<reqwest::Client> {
+    let mut builder = reqwest::Client::builder();
+
+    if !config.proxy.is_empty() {
+        let mut proxy = reqwest::Proxy::all(&config.proxy)
+            .with_context(|| format!("Invalid proxy URL {:?}", config.proxy))?;
+        if !config.no_proxy.is_empty() {
+            proxy = proxy.no_proxy(reqwest::NoProxy::from_string(&config.no_proxy.join(",")));
+        }
+        builder = builder.proxy(proxy);
+    }
+
+    if !config.ca_bundle.is_empty() {
+        let path = expand_home(&config.ca_bundle);
+        let pem = std::fs::read(&path)
+            .with_context(|| format!("Failed to read CA bundle {}", path.display()))?;
+        let certificates = reqwest::Certificate::from_pem_bundle(&pem)
+            .with_context(|| format!("Invalid CA bundle {}", path.display()))?;
+        if certificates.is_empty() {
+            bail!("No certificates found in {}", path.display());
+        }
+        for certificate in certificates {
+            builder = builder.add_root_certificate(certificate);
+        }
+    }
+
+    if !config.client_cert.is_empty() {
+        builder = builder.identity(load_identity(config)?);
+    }
+
+    builder.build().context("Failed to set up the HTTP client")
+}
+
+/// Load the client certificate and its key
+fn load_identity(config: &NetworkConfig) -> Result<reqwest::Identity> {
+    let cert_path = expand_home(&config.client_cert);
+    let cert = std::fs::read(&cert_path)
+        .with_context(|| format!("Failed to read client certificate {}", cert_path.display()))?;
+
+    let is_pkcs12 = cert_path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("p12") || ext.eq_ignore_ascii_case("pfx"));
+    if is_pkcs12 {
+        if secrets::parse_reference(&config.client_cert_password).is_some() {
+            bail!("network.client_cert_password could not be read from the system keyring");
+        }
+        return reqwest::Identity::from_pkcs12_der(&cert, &config.client_cert_password)
+            .with_context(|| format!("Invalid PKCS#12 file {}", cert_path.display()));
+    }
+
+    if config.client_key.is_empty() {
+        bail!("network.client_key is required for a PEM client certificate");
+    }
+    let key_path = expand_home(&config.client_key);
+    let key = std::fs::read(&key_path)
+        .with_context(|| format!("Failed to read client key {}", key_path.display()))?;
+    reqwest::Identity::from_pkcs8_pem(&cert, &key).with_context(|| {
+        format!(
+            "Invalid client certificate {} or key {} (the key must be PKCS#8)",
+            cert_path.display(),
+            key_path.display()
+        )
+    })
+}
+
+/// Resolve a leading `~/` to the home directory
+fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), directories::BaseDirs::new()) {
+        (Some(rest), Some(dirs)) => dirs.home_dir().join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bad_settings_are_reported() {
+        assert!(build_client(&NetworkConfig::default()).is_ok());
+
+        let proxy = NetworkConfig {
+            proxy: "http://proxy.example:3128".to_string(),
+            no_proxy: vec!["localhost".to_string()],
+            ..Default::default()
+        };
+        assert!(build_client(&proxy).is_ok());
+
+        let missing = NetworkConfig {
+            ca_bundle: "/nonexistent/ca.pem".to_string(),
+            ..Default::default()
+        };
+        let error = format!("{:#}", build_client(&missing).unwrap_err());
+        assert!(error.contains("/nonexistent/ca.pem"));
+
+        let no_key = NetworkConfig {
+            client_cert: "/nonexistent/client.pem".to_string(),
+            ..Default::default()
+        };
+        assert!(build_client(&no_key).is_err());
+    }
+}
@@ -0,0 +1,264 @@
+//! API keys to rotate through when quotas run out
+//!
+//! Keys are tried in the configured order. A key that gets a 429 response is
+//! skipped until its quota resets. With a state file, used-up daily quotas
+//! are remembered across runs (only hashes of the keys are written); waits
+//! of a minute or so only last for the current run, so a single key isn't
+//! refused on the next start.
+
+use super::cache::fnv1a;
+use crate::config::settings::{cache_dir, write_private};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// How long a key is skipped when the response doesn't say
+const DEFAULT_QUOTA_DELAY: Duration = Duration::from_secs(60);
+
+/// Daily Gemini quotas reset at midnight Pacific time, at the latest 08:00 UTC
+const DAILY_RESET_HOUR_UTC: u64 = 8;
+
+/// When a used-up quota is available again
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QuotaReset {
+    pub at: SystemTime,
+    /// A daily quota, which is worth remembering across runs
+    pub daily: bool,
+}
+
+/// A set of API keys with their quota state
+#[derive(Debug)]
+pub struct KeyPool {
+    keys: Vec<String>,
+    /// Key hash to when its quota resets
+    exhausted: Mutex<HashMap<String, QuotaReset>>,
+    state_file: Option<PathBuf>,
+}
+
+impl KeyPool {
+    /// Create a pool from keys in order of preference, skipping empty and duplicate ones
+    pub fn new(keys: Vec<String>) -> Self {
+        let mut unique: Vec<String> = Vec::new();
+        for key in keys {
+            if !key.is_empty() && !unique.contains(&key) {
+                unique.push(key);
+            }
+        }
+        Self {
+            keys: unique,
+            exhausted: Mutex::new(HashMap::new()),
+            state_file: None,
+        }
+    }
+
+    /// Remember exhausted keys in a file shared with other runs
+    pub fn with_state_file(mut self, path: PathBuf) -> Self {
+        let state: HashMap<String, u64> = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+        let state = state.into_iter().map(|(id, until)| {
+            let reset = QuotaReset {
+                at: UNIX_EPOCH + Duration::from_secs(until),
+                daily: true,
+            };
+            (id, reset)
+        });
+        self.exhausted = Mutex::new(state.collect());
+        self.state_file = Some(path);
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// The first key whose quota isn't used up
+    ///
+    /// Otherwise returns when the next key becomes available again.
+    pub fn available(&self) -> Result<&str, SystemTime> {
+        let now = SystemTime::now();
+        let exhausted = self.exhausted.lock().unwrap_or_else(|e| e.into_inner());
+        let mut next_reset: Option<SystemTime> = None;
+        for key in &self.keys {
+            match exhausted.get(&key_id(key)) {
+                Some(reset) if reset.at > now => {
+                    next_reset = Some(next_reset.map_or(reset.at, |next| next.min(reset.at)))
+                }
+                _ => return Ok(key),
+            }
+        }
+        Err(next_reset.unwrap_or(now))
+    }
+
+    /// Skip a key until its quota resets
+    pub fn mark_exhausted(&self, key: &str, reset: QuotaReset) {
+        let mut exhausted = self.exhausted.lock().unwrap_or_else(|e| e.into_inner());
+        let now = SystemTime::now();
+        exhausted.retain(|_, reset| reset.at > now);
+        exhausted.insert(key_id(key), reset);
+        if !reset.daily {
+            return;
+        }
+
+        if let Some(ref path) = self.state_file {
+            let daily: HashMap<&String, u64> = exhausted
+                .iter()
+                .filter(|(_, reset)| reset.daily)
+                .map(|(id, reset)| (id, unix_secs(reset.at)))
+                .collect();
+            let result = path
+                .parent()
+                .map_or(Ok(()), std::fs::create_dir_all)
+                .and_then(|()| write_private(path, &serde_json::to_string(&daily)?));
+            if let Err(e) = result {
+                tracing::warn!("Failed to save quota state to {}: {}", path.display(), e);
+            }
+        }
+    }
+}
+
+/// File remembering exhausted keys between runs
+pub fn quota_state_path() -> Option<PathBuf> {
+    cache_dir().map(|dir| dir.join("exhausted_keys.json"))
+}
+
+/// When the quota behind a 429 response body resets
+///
+/// Daily quotas reset the next day; otherwise the `RetryInfo` delay is used.
+pub fn quota_reset(body: &str, now: SystemTime) -> QuotaReset {
+    let details = serde_json::from_str::<serde_json::Value>(body)
+        .ok()
+        .and_then(|value| value["error"]["details"].as_array().cloned())
+        .unwrap_or_default();
+
+    let daily = details
+        .iter()
+        .filter_map(|detail| detail["violations"].as_array())
+        .flatten()
+        .filter_map(|violation| violation["quotaId"].as_str())
+        .any(|quota| quota.contains("PerDay"));
+    if daily {
+        let secs = unix_secs(now);
+        let reset_today = secs - secs % 86_400 + DAILY_RESET_HOUR_UTC * 3600;
+        let reset = if reset_today > secs {
+            reset_today
+        } else {
+            reset_today + 86_400
+        };
+        return QuotaReset {
+            at: UNIX_EPOCH + Duration::from_secs(reset),
+            daily: true,
+        };
+    }
+
+    let delay = details
+        .iter()
+        .filter_map(|detail| detail["retryDelay"].as_str())
+        .filter_map(|delay| delay.strip_suffix('s')?.parse::<f64>().ok())
+        .find(|secs| secs.is_finite() && *secs >= 0.0)
+        .map(|secs| Duration::from_secs_f64(secs.ceil()))
+        .unwrap_or(DEFAULT_QUOTA_DELAY);
+    QuotaReset {
+        at: now + delay,
+        daily: false,
+    }
+}
+
+/// Identify a key in the state file without storing it
+fn key_id(key: &str) -> String {
+    format!("{:016x}", fnv1a(key.as_bytes()))
+}
+
+fn unix_secs(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn until(at: SystemTime) -> QuotaReset {
+        QuotaReset { at, daily: false }
+    }
+
+    #[test]
+    fn exhausted_keys_are_skipped_until_reset() {
+        let pool = KeyPool::new(vec!["a".into(), "".into(), "b".into(), "a".into()]);
+        assert_eq!(pool.len(), 2);
+        assert_eq!(pool.available(), Ok("a"));
+
+        let later = SystemTime::now() + Duration::from_secs(600);
+        pool.mark_exhausted("a", until(later));
+        assert_eq!(pool.available(), Ok("b"));
+
+        pool.mark_exhausted("b", until(later + Duration::from_secs(60)));
+        assert_eq!(pool.available(), Err(later));
+
+        // An expired entry makes the key usable again
+        pool.mark_exhausted("a", until(SystemTime::now() - Duration::from_secs(1)));
+        assert_eq!(pool.available(), Ok("a"));
+    }
+
+    #[test]
+    fn only_daily_quotas_are_remembered_across_runs() {
+        let path = std::env::temp_dir().join(format!("akkurate-quota-{}.json", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let later = SystemTime::now() + Duration::from_secs(600);
+
+        let pool = KeyPool::new(vec!["a".into(), "b".into()]).with_state_file(path.clone());
+        pool.mark_exhausted("a", until(later));
+        pool.mark_exhausted(
+            "b",
+            QuotaReset {
+                at: later,
+                daily: true,
+            },
+        );
+
+        let next_run = KeyPool::new(vec!["a".into(), "b".into()]).with_state_file(path.clone());
+        assert_eq!(next_run.available(), Ok("a"));
+        next_run.mark_exhausted("a", until(later));
+        assert_eq!(
+            unix_secs(next_run.available().unwrap_err()),
+            unix_secs(later)
+        );
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn reset_time_comes_from_the_response() {
+        let now = UNIX_EPOCH + Duration::from_secs(1_760_000_000);
+        let retry = r#"{"error": {"code": 429, "details": [
+            {"@type": "type.googleapis.com/google.rpc.RetryInfo", "retryDelay": "37.2s"}
+        ]}}"#;
+        assert_eq!(
+            quota_reset(retry, now),
+            until(now + Duration::from_secs(38))
+        );
+
+        let daily = r#"{"error": {"code": 429, "details": [
+            {"@type": "type.googleapis.com/google.rpc.QuotaFailure", "violations": [
+                {"quotaId": "GenerateRequestsPerDayPerProjectPerModel-FreeTier"}
+            ]},
+            {"@type": "type.googleapis.com/google.rpc.RetryInfo", "retryDelay": "5s"}
+        ]}}"#;
+        let reset = quota_reset(daily, now);
+        assert!(reset.daily);
+        let reset = unix_secs(reset.at);
+        assert_eq!(reset % 86_400, DAILY_RESET_HOUR_UTC * 3600);
+        assert!(reset > unix_secs(now) && reset <= unix_secs(now) + 86_400);
+
+        assert_eq!(
+            quota_reset("not json", now),
+            until(now + DEFAULT_QUOTA_DELAY)
+        );
+    }
+}
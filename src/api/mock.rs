@@ -0,0 +1,65 @@
+//! Canned results instead of API requests
+//!
+//! Used by `provider = "mock"` to try the interface without a key, and by
+//! tests that need predictable answers.
+
+use super::gemini::{CheckResult, EnhanceResult};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Provider answering from a table of canned results
+///
+/// Texts without a canned result come back unchanged and without issues.
+#[derive(Debug, Default)]
+pub struct MockProvider {
+    check_results: HashMap<String, CheckResult>,
+    enhance_results: HashMap<String, EnhanceResult>,
+    calls: AtomicUsize,
+}
+
+impl MockProvider {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Answer checks of `text` with `result`
+    pub fn with_check_result(mut self, text: impl Into<String>, result: CheckResult) -> Self {
+        self.check_results.insert(text.into(), result);
+        self
+    }
+
+    /// Answer enhancements of `text` with `result`
+    pub fn with_enhance_result(mut self, text: impl Into<String>, result: EnhanceResult) -> Self {
+        self.enhance_results.insert(text.into(), result);
+        self
+    }
+
+    pub fn check(&self, text: &str) -> CheckResult {
+        self.calls.fetch_add(1, Ordering::Relaxed);
+        self.check_results
+            .get(text)
+            .cloned()
+            .unwrap_or_else(|| CheckResult {
+                issues: Vec::new(),
+                corrected_text: text.to_string(),
+                summary: None,
+                unchecked: Vec::new(),
+            })
+    }
+
+    pub fn enhance(&self, text: &str) -> EnhanceResult {
+        self.calls.fetch_add(1, Ordering::Relaxed);
+        self.enhance_results
+            .get(text)
+            .cloned()
+            .unwrap_or_else(|| EnhanceResult {
+                enhanced_text: text.to_string(),
+                changes_made: Vec::new(),
+            })
+    }
+
+    /// Number of checks and enhancements answered so far
+    pub fn calls(&self) -> usize {
+        self.calls.load(Ordering::Relaxed)
+    }
+}
@@ -1,5 +1,16 @@
 //! API module for external service integrations
 
+pub mod cache;
 pub mod gemini;
+pub mod http;
+pub mod keys;
+pub mod mock;
+pub mod replay;
+pub mod usage;
 
-pub use gemini::{CheckResult, EnhanceResult, GeminiClient};
+pub use cache::{clear_cache, CacheKey, ResultCache};
+pub use gemini::{CheckResult, EnhanceResult, GeminiClient, GrammarIssue};
+pub use keys::KeyPool;
+pub use mock::MockProvider;
+pub use replay::{FixtureMode, Fixtures};
+pub use usage::{TokenUsage, UsageLog};
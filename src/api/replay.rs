@@ -0,0 +1,116 @@
+//! Recording API responses to fixture files and replaying them
+//!
+//! In record mode every successful response is saved next to the request
+//! that produced it; in replay mode responses are read back from those files
+//! and nothing is sent. Fixtures are matched by the same key as the result
+//! cache, so the random delimiter in each request doesn't matter, except
+//! that the endpoint is left out: fixtures recorded against a gateway replay
+//! anywhere.
+
+use super::cache::CacheKey;
+use crate::config::settings::write_private;
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// Whether fixtures are written or read
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FixtureMode {
+    Record,
+    Replay,
+}
+
+/// A directory of recorded request/response pairs
+#[derive(Debug, Clone)]
+pub struct Fixtures {
+    dir: PathBuf,
+    mode: FixtureMode,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Fixture {
+    key: CacheKey,
+    /// The request as sent, for reference
+    request: serde_json::Value,
+    /// The response body
+    response: serde_json::Value,
+}
+
+impl Fixtures {
+    /// Save responses to files in `dir`
+    pub fn record(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            mode: FixtureMode::Record,
+        }
+    }
+
+    /// Answer requests from files in `dir`
+    pub fn replay(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            mode: FixtureMode::Replay,
+        }
+    }
+
+    /// Parse a `record:<dir>` or `replay:<dir>` setting
+    pub fn parse(spec: &str) -> Result<Self> {
+        match spec.split_once(':') {
+            Some(("record", dir)) if !dir.is_empty() => Ok(Self::record(dir)),
+            Some(("replay", dir)) if !dir.is_empty() => Ok(Self::replay(dir)),
+            _ => bail!("Expected record:<dir> or replay:<dir>, got {:?}", spec),
+        }
+    }
+
+    pub fn mode(&self) -> FixtureMode {
+        self.mode
+    }
+
+    /// Read the recorded response body for a request
+    pub(crate) fn load(&self, key: &CacheKey) -> Result<String> {
+        let key = &fixture_key(key);
+        let path = self.dir.join(key.file_name());
+        let content = std::fs::read_to_string(&path).with_context(|| {
+            format!(
+                "No recorded response for this request (expected {})",
+                path.display()
+            )
+        })?;
+        let fixture: Fixture = serde_json::from_str(&content)
+            .with_context(|| format!("Invalid fixture {}", path.display()))?;
+        if fixture.key != *key {
+            bail!("Fixture {} belongs to a different request", path.display());
+        }
+        Ok(fixture.response.to_string())
+    }
+
+    /// Save a response body along with its request
+    ///
+    /// The files hold the texts sent, so they are readable only by the owner.
+    pub(crate) fn save(
+        &self,
+        key: &CacheKey,
+        request: serde_json::Value,
+        response: &str,
+    ) -> Result<()> {
+        let key = &fixture_key(key);
+        let fixture = Fixture {
+            key: key.clone(),
+            request,
+            response: serde_json::from_str(response).context("Response is not JSON")?,
+        };
+        std::fs::create_dir_all(&self.dir)
+            .with_context(|| format!("Failed to create {}", self.dir.display()))?;
+        let path = self.dir.join(key.file_name());
+        write_private(&path, &(serde_json::to_string_pretty(&fixture)? + "\n"))
+            .with_context(|| format!("Failed to write {}", path.display()))
+    }
+}
+
+/// The key of a request without the endpoint
+fn fixture_key(key: &CacheKey) -> CacheKey {
+    CacheKey {
+        endpoint: String::new(),
+        ..key.clone()
+    }
+}
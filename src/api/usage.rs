@@ -0,0 +1,248 @@
+//! Token usage and cost tracking
+//!
+//! Token counts reported by the API are added up per day (UTC) and per
+//! provider/model in `~/.local/share/akkurate/usage.json`.
+
+use crate::config::settings::{data_dir, write_private, BudgetAction, UsageConfig};
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Requests and tokens used
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TokenUsage {
+    pub requests: u64,
+    pub prompt_tokens: u64,
+    pub output_tokens: u64,
+}
+
+impl TokenUsage {
+    pub fn add(&mut self, other: TokenUsage) {
+        self.requests += other.requests;
+        self.prompt_tokens += other.prompt_tokens;
+        self.output_tokens += other.output_tokens;
+    }
+
+    /// Estimated cost in USD with the configured prices
+    pub fn cost(&self, prices: &UsageConfig) -> f64 {
+        (self.prompt_tokens as f64 * prices.input_price
+            + self.output_tokens as f64 * prices.output_price)
+            / 1_000_000.0
+    }
+}
+
+/// Daily usage totals
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UsageLog {
+    /// Date (`YYYY-MM-DD`, UTC) to `provider/model` to usage
+    pub days: BTreeMap<String, BTreeMap<String, TokenUsage>>,
+}
+
+impl UsageLog {
+    /// Load the usage file, starting empty if it is missing or unreadable
+    pub fn load() -> Self {
+        let Some(path) = usage_path() else {
+            return Self::default();
+        };
+        match std::fs::read_to_string(&path) {
+            Ok(content) => serde_json::from_str(&content).unwrap_or_else(|e| {
+                tracing::warn!("Ignoring unreadable usage file {}: {}", path.display(), e);
+                Self::default()
+            }),
+            Err(_) => Self::default(),
+        }
+    }
+
+    fn save(&self, path: &Path) -> Result<()> {
+        let json = serde_json::to_string_pretty(self)?;
+        write_private(path, &json).with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    /// Add usage for today to the usage file
+    ///
+    /// Every process holds a lock while it reads and rewrites the file, so
+    /// requests finishing at the same time in two windows are both counted.
+    pub fn record(model: &str, usage: TokenUsage) -> Result<()> {
+        let path = usage_path().context("Could not determine data directory")?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)
+                .with_context(|| format!("Failed to create {}", dir.display()))?;
+        }
+        let _lock = lock(&path)?;
+        let mut log = Self::load();
+        log.add(&today(), model, usage);
+        log.save(&path)
+    }
+
+    fn add(&mut self, date: &str, model: &str, usage: TokenUsage) {
+        self.days
+            .entry(date.to_string())
+            .or_default()
+            .entry(model.to_string())
+            .or_default()
+            .add(usage);
+    }
+
+    /// Total of one day across all models
+    pub fn day_total(&self, date: &str) -> TokenUsage {
+        let mut total = TokenUsage::default();
+        for usage in self.days.get(date).into_iter().flat_map(|m| m.values()) {
+            total.add(*usage);
+        }
+        total
+    }
+
+    /// Total of a month (`YYYY-MM`) across all models
+    pub fn month_total(&self, month: &str) -> TokenUsage {
+        let mut total = TokenUsage::default();
+        for (date, models) in &self.days {
+            if date.starts_with(month) {
+                for usage in models.values() {
+                    total.add(*usage);
+                }
+            }
+        }
+        total
+    }
+
+    /// Cost so far this month, if it has reached the budget
+    pub fn over_budget(&self, config: &UsageConfig) -> Option<f64> {
+        if config.monthly_budget <= 0.0 {
+            return None;
+        }
+        let spent = self.month_total(&this_month()).cost(config);
+        (spent >= config.monthly_budget).then_some(spent)
+    }
+}
+
+/// Refuse a request if the budget is used up and set to block
+///
+/// When set to warn, the request goes ahead and a warning is logged.
+pub fn check_budget(config: &UsageConfig) -> Result<()> {
+    let Some(spent) = UsageLog::load().over_budget(config) else {
+        return Ok(());
+    };
+    match config.on_budget_exceeded {
+        BudgetAction::Block => anyhow::bail!(
+            "Monthly budget of ${:.2} reached (${:.2} spent); \
+             raise usage.monthly_budget to continue",
+            config.monthly_budget,
+            spent
+        ),
+        BudgetAction::Warn => {
+            tracing::warn!(
+                "Monthly budget of ${:.2} exceeded (${:.2} spent)",
+                config.monthly_budget,
+                spent
+            );
+            Ok(())
+        }
+    }
+}
+
+/// Take an exclusive lock for changing the file at `path`, released on drop
+///
+/// The lock is on a separate file, as saving replaces the file itself.
+fn lock(path: &Path) -> Result<File> {
+    let mut lock_path = path.as_os_str().to_owned();
+    lock_path.push(".lock");
+    let lock_path = PathBuf::from(lock_path);
+    let file = File::options()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(&lock_path)
+        .with_context(|| format!("Failed to open {}", lock_path.display()))?;
+    file.lock()
+        .with_context(|| format!("Failed to lock {}", lock_path.display()))?;
+    Ok(file)
+}
+
+/// Path of the usage file
+pub fn usage_path() -> Option<PathBuf> {
+    data_dir().map(|dir| dir.join("usage.json"))
+}
+
+/// Today's date in UTC as `YYYY-MM-DD`
+pub fn today() -> String {
+    date_string(SystemTime::now())
+}
+
+/// The current month in UTC as `YYYY-MM`
+pub fn this_month() -> String {
+    today()[..7].to_string()
+}
+
+/// Format the UTC date of a point in time as `YYYY-MM-DD`
+pub fn date_string(time: SystemTime) -> String {
+    let days = time
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() / 86_400)
+        .unwrap_or(0) as i64;
+    let (year, month, day) = civil_from_days(days);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+/// Convert days since 1970-01-01 to a (year, month, day) date
+///
+/// Howard Hinnant's `civil_from_days` algorithm for the proleptic Gregorian calendar.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn dates_are_formatted_in_utc() {
+        assert_eq!(date_string(UNIX_EPOCH), "1970-01-01");
+        // 2024-02-29 12:00 UTC
+        let leap_day = UNIX_EPOCH + Duration::from_secs(1_709_208_000);
+        assert_eq!(date_string(leap_day), "2024-02-29");
+        let new_year = UNIX_EPOCH + Duration::from_secs(1_735_689_600);
+        assert_eq!(date_string(new_year), "2025-01-01");
+    }
+
+    #[test]
+    fn totals_add_up_per_day_and_month() {
+        let mut log = UsageLog::default();
+        let usage = TokenUsage {
+            requests: 1,
+            prompt_tokens: 1000,
+            output_tokens: 200,
+        };
+        log.add("2026-10-01", "gemini/a", usage);
+        log.add("2026-10-01", "gemini/b", usage);
+        log.add("2026-10-18", "gemini/a", usage);
+        log.add("2026-09-30", "gemini/a", usage);
+
+        assert_eq!(log.day_total("2026-10-01").requests, 2);
+        let month = log.month_total("2026-10");
+        assert_eq!(month.requests, 3);
+        assert_eq!(month.prompt_tokens, 3000);
+
+        let prices = UsageConfig {
+            input_price: 1.0,
+            output_price: 10.0,
+            ..Default::default()
+        };
+        // 3000 * $1/M + 600 * $10/M
+        assert!((month.cost(&prices) - 0.009).abs() < 1e-9);
+    }
+}
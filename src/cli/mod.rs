@@ -0,0 +1,5 @@
+//! Command-line subcommands
+
+pub mod config;
+pub mod presets;
+pub mod stats;
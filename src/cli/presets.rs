@@ -0,0 +1,25 @@
+//! `akkurate presets` subcommand
+
+use crate::config::{load_checked, ConfigOverrides};
+
+/// Print the presets in picker order and return the process exit code
+pub fn list(overrides: &ConfigOverrides, all: bool) -> i32 {
+    let checked = load_checked(overrides);
+    let language = &checked.config.preferences.language;
+
+    for (key, preset) in checked.presets.all() {
+        if preset.hidden && !all {
+            continue;
+        }
+        let mut line = format!("{:<12} {}", key, preset.label(language));
+        if let Some(ref description) = preset.description {
+            line.push_str(" - ");
+            line.push_str(description);
+        }
+        if preset.hidden {
+            line.push_str(" (hidden)");
+        }
+        println!("{}", line);
+    }
+    0
+}
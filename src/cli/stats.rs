@@ -0,0 +1,63 @@
+//! `akkurate stats` subcommand
+
+use crate::api::usage::{this_month, usage_path, TokenUsage, UsageLog};
+use crate::config::{load_checked, ConfigOverrides};
+
+/// Print token usage per day and model and return the process exit code
+pub fn show(overrides: &ConfigOverrides, days: usize) -> i32 {
+    let prices = load_checked(overrides).config.usage;
+    let log = UsageLog::load();
+    let with_cost = prices.input_price > 0.0 || prices.output_price > 0.0;
+    let cost = |usage: &TokenUsage| {
+        if with_cost {
+            format!("${:.4}", usage.cost(&prices))
+        } else {
+            "-".to_string()
+        }
+    };
+
+    if log.days.is_empty() {
+        let path = usage_path()
+            .map(|p| p.display().to_string())
+            .unwrap_or_else(|| "usage.json".to_string());
+        println!("No usage recorded yet ({})", path);
+        return 0;
+    }
+
+    println!(
+        "{:<10}  {:<32} {:>8} {:>12} {:>12} {:>10}",
+        "Date", "Model", "Requests", "Prompt", "Output", "Cost"
+    );
+    for (date, models) in log.days.iter().rev().take(days) {
+        for (model, usage) in models {
+            println!(
+                "{:<10}  {:<32} {:>8} {:>12} {:>12} {:>10}",
+                date,
+                model,
+                usage.requests,
+                usage.prompt_tokens,
+                usage.output_tokens,
+                cost(usage)
+            );
+        }
+    }
+
+    let month = this_month();
+    let total = log.month_total(&month);
+    let mut summary = format!(
+        "\n{}: {} requests, {} prompt tokens, {} output tokens",
+        month, total.requests, total.prompt_tokens, total.output_tokens
+    );
+    if with_cost {
+        summary.push_str(&format!(", {}", cost(&total)));
+        if prices.monthly_budget > 0.0 {
+            summary.push_str(&format!(" of ${:.2} budget", prices.monthly_budget));
+        }
+    }
+    println!("{}", summary);
+
+    if log.over_budget(&prices).is_some() {
+        eprintln!("Warning: the monthly budget is used up");
+    }
+    0
+}
@@ -0,0 +1,300 @@
+//! Clipboard backends and session detection
+
+use super::mime::{self, MimePreference};
+use super::Selection;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+/// A way of talking to the system clipboard
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backend {
+    /// `wl-paste` / `wl-copy` (Wayland)
+    WlClipboard,
+    /// `xclip` (X11 / XWayland)
+    Xclip,
+    /// `xsel` (X11 / XWayland)
+    Xsel,
+    /// `arboard` crate, used when no command-line tool is available
+    Arboard,
+}
+
+impl Backend {
+    /// Detect usable backends for the current session, in order of preference
+    ///
+    /// Wayland sessions prefer `wl-clipboard`. When `DISPLAY` is set (native X11
+    /// or XWayland) the X11 tools are tried next, so the app keeps working on
+    /// compositors without `wl-clipboard`. `arboard` is always last.
+    pub fn detect() -> Vec<Backend> {
+        let mut backends = Vec::new();
+
+        #[cfg(target_os = "linux")]
+        {
+            if env_is_set("WAYLAND_DISPLAY") && command_exists("wl-paste") {
+                backends.push(Backend::WlClipboard);
+            }
+            if env_is_set("DISPLAY") {
+                if command_exists("xclip") {
+                    backends.push(Backend::Xclip);
+                }
+                if command_exists("xsel") {
+                    backends.push(Backend::Xsel);
+                }
+            }
+        }
+
+        backends.push(Backend::Arboard);
+        tracing::debug!("Clipboard backends: {:?}", backends);
+        backends
+    }
+
+    /// Get the backend name for logging
+    pub fn name(&self) -> &'static str {
+        match self {
+            Backend::WlClipboard => "wl-clipboard",
+            Backend::Xclip => "xclip",
+            Backend::Xsel => "xsel",
+            Backend::Arboard => "arboard",
+        }
+    }
+
+    /// Read text from a selection, negotiating the content type first
+    ///
+    /// HTML content is converted to plain text before it is returned.
+    pub fn read(&self, selection: Selection, preference: MimePreference) -> Option<String> {
+        let offered = self.list_types(selection);
+        let mime = offered
+            .as_deref()
+            .and_then(|types| mime::negotiate(types, preference));
+
+        let mut command = match (self, selection) {
+            (Backend::WlClipboard, Selection::Primary) => {
+                command("wl-paste", &["--primary", "--no-newline"])
+            }
+            (Backend::WlClipboard, Selection::Clipboard) => command("wl-paste", &["--no-newline"]),
+            (Backend::Xclip, _) => command("xclip", &["-selection", &selection.to_string()]),
+            (Backend::Xsel, Selection::Primary) => command("xsel", &["--primary", "--output"]),
+            (Backend::Xsel, Selection::Clipboard) => command("xsel", &["--clipboard", "--output"]),
+            (Backend::Arboard, _) => return read_arboard(selection),
+        };
+        match (self, mime) {
+            (Backend::WlClipboard, Some(mime)) => {
+                command.args(["--type", mime]);
+            }
+            (Backend::Xclip, Some(mime)) => {
+                command.args(["-t", mime]);
+            }
+            _ => {}
+        }
+        if *self == Backend::Xclip {
+            command.arg("-o");
+        }
+
+        let Some(text) = output_text(&mut command) else {
+            tracing::warn!("{} read from {} failed", self.name(), selection);
+            return None;
+        };
+
+        match mime {
+            Some(mime) if mime::is_html(mime) => Some(mime::html_to_text(&text)),
+            _ => Some(text),
+        }
+    }
+
+    /// Write plain UTF-8 text to a selection
+    pub fn write(&self, selection: Selection, text: &str) -> bool {
+        let mut command = match (self, selection) {
+            (Backend::WlClipboard, Selection::Primary) => {
+                command("wl-copy", &["--primary", "--type", mime::TEXT_PLAIN_UTF8])
+            }
+            (Backend::WlClipboard, Selection::Clipboard) => {
+                command("wl-copy", &["--type", mime::TEXT_PLAIN_UTF8])
+            }
+            (Backend::Xclip, _) => command(
+                "xclip",
+                &[
+                    "-selection",
+                    &selection.to_string(),
+                    "-t",
+                    "UTF8_STRING",
+                    "-i",
+                ],
+            ),
+            (Backend::Xsel, Selection::Primary) => command("xsel", &["--primary", "--input"]),
+            (Backend::Xsel, Selection::Clipboard) => command("xsel", &["--clipboard", "--input"]),
+            (Backend::Arboard, _) => return write_arboard(selection, text),
+        };
+
+        // The tools fork to keep serving the selection, so the output pipes
+        // must not be inherited or `wait` would never return.
+        let Ok(mut child) = command
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+        else {
+            tracing::warn!("Failed to spawn {}", self.name());
+            return false;
+        };
+
+        let written = child
+            .stdin
+            .take()
+            .is_some_and(|mut stdin| stdin.write_all(text.as_bytes()).is_ok());
+        let exited_ok = child.wait().is_ok_and(|status| status.success());
+
+        if written && exited_ok {
+            tracing::info!("{} write to {} successful", self.name(), selection);
+            true
+        } else {
+            tracing::warn!("{} write to {} failed", self.name(), selection);
+            false
+        }
+    }
+
+    /// Empty a selection
+    pub fn clear(&self, selection: Selection) -> bool {
+        let mut command = match (self, selection) {
+            (Backend::WlClipboard, Selection::Primary) => {
+                command("wl-copy", &["--primary", "--clear"])
+            }
+            (Backend::WlClipboard, Selection::Clipboard) => command("wl-copy", &["--clear"]),
+            // xclip has no clear option; owning the selection with no data empties it
+            (Backend::Xclip, _) => return self.write(selection, ""),
+            (Backend::Xsel, Selection::Primary) => command("xsel", &["--primary", "--clear"]),
+            (Backend::Xsel, Selection::Clipboard) => command("xsel", &["--clipboard", "--clear"]),
+            (Backend::Arboard, _) => return clear_arboard(selection),
+        };
+
+        let cleared = command
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .is_ok_and(|status| status.success());
+        if !cleared {
+            tracing::warn!("{} clear of {} failed", self.name(), selection);
+        }
+        cleared
+    }
+
+    /// List the MIME types (or X11 targets) a selection currently offers
+    ///
+    /// Returns `None` when the backend cannot list types.
+    fn list_types(&self, selection: Selection) -> Option<Vec<String>> {
+        let mut command = match (self, selection) {
+            (Backend::WlClipboard, Selection::Primary) => {
+                command("wl-paste", &["--primary", "--list-types"])
+            }
+            (Backend::WlClipboard, Selection::Clipboard) => command("wl-paste", &["--list-types"]),
+            (Backend::Xclip, _) => command(
+                "xclip",
+                &["-selection", &selection.to_string(), "-t", "TARGETS", "-o"],
+            ),
+            (Backend::Xsel, _) | (Backend::Arboard, _) => return None,
+        };
+
+        let types = output_text(&mut command)?;
+        Some(types.lines().map(|line| line.trim().to_string()).collect())
+    }
+}
+
+fn command(program: &str, args: &[&str]) -> Command {
+    let mut command = Command::new(program);
+    command.args(args);
+    command
+}
+
+/// Run a command and return its stdout if it succeeded with valid UTF-8
+fn output_text(command: &mut Command) -> Option<String> {
+    command
+        .stderr(Stdio::null())
+        .output()
+        .ok()
+        .filter(|o| o.status.success())
+        .and_then(|o| String::from_utf8(o.stdout).ok())
+}
+
+#[cfg(target_os = "linux")]
+fn linux_kind(selection: Selection) -> arboard::LinuxClipboardKind {
+    match selection {
+        Selection::Primary => arboard::LinuxClipboardKind::Primary,
+        Selection::Clipboard => arboard::LinuxClipboardKind::Clipboard,
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn read_arboard(selection: Selection) -> Option<String> {
+    use arboard::GetExtLinux;
+
+    let mut clipboard = arboard::Clipboard::new().ok()?;
+    clipboard.get().clipboard(linux_kind(selection)).text().ok()
+}
+
+#[cfg(not(target_os = "linux"))]
+fn read_arboard(selection: Selection) -> Option<String> {
+    if selection == Selection::Primary {
+        return None;
+    }
+    let mut clipboard = arboard::Clipboard::new().ok()?;
+    clipboard.get_text().ok()
+}
+
+#[cfg(target_os = "linux")]
+fn write_arboard(selection: Selection, text: &str) -> bool {
+    use arboard::SetExtLinux;
+
+    match arboard::Clipboard::new() {
+        Ok(mut clipboard) => clipboard
+            .set()
+            .clipboard(linux_kind(selection))
+            .text(text)
+            .is_ok(),
+        Err(_) => {
+            tracing::warn!("Failed to initialize arboard clipboard");
+            false
+        }
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn write_arboard(selection: Selection, text: &str) -> bool {
+    if selection == Selection::Primary {
+        return false;
+    }
+    match arboard::Clipboard::new() {
+        Ok(mut clipboard) => clipboard.set_text(text).is_ok(),
+        Err(_) => {
+            tracing::warn!("Failed to initialize arboard clipboard");
+            false
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn clear_arboard(selection: Selection) -> bool {
+    use arboard::ClearExtLinux;
+
+    arboard::Clipboard::new()
+        .and_then(|mut clipboard| clipboard.clear_with().clipboard(linux_kind(selection)))
+        .is_ok()
+}
+
+#[cfg(not(target_os = "linux"))]
+fn clear_arboard(selection: Selection) -> bool {
+    selection == Selection::Clipboard
+        && arboard::Clipboard::new()
+            .and_then(|mut clipboard| clipboard.clear())
+            .is_ok()
+}
+
+#[cfg(target_os = "linux")]
+fn env_is_set(name: &str) -> bool {
+    std::env::var_os(name).is_some_and(|value| !value.is_empty())
+}
+
+/// Check whether an executable is available on `PATH`
+#[cfg(target_os = "linux")]
+fn command_exists(program: &str) -> bool {
+    std::env::var_os("PATH")
+        .is_some_and(|paths| std::env::split_paths(&paths).any(|dir| dir.join(program).is_file()))
+}
@@ -0,0 +1,209 @@
+//! MIME type negotiation for clipboard content
+
+use serde::{Deserialize, Serialize};
+
+/// Plain UTF-8 text, used for everything the app writes
+pub const TEXT_PLAIN_UTF8: &str = "text/plain;charset=utf-8";
+
+/// HTML fragments offered by browsers and office suites
+pub const TEXT_HTML: &str = "text/html";
+
+/// Plain text types in order of preference (Wayland MIME types and X11 targets)
+const PLAIN_TYPES: &[&str] = &[
+    TEXT_PLAIN_UTF8,
+    "UTF8_STRING",
+    "text/plain",
+    "STRING",
+    "TEXT",
+];
+
+/// Content type to prefer when a selection offers both plain text and HTML
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MimePreference {
+    #[default]
+    Plain,
+    Html,
+}
+
+/// Pick the type to request from the types a selection offers
+pub fn negotiate(offered: &[String], preference: MimePreference) -> Option<&str> {
+    let find = |candidates: &[&str]| {
+        candidates.iter().find_map(|candidate| {
+            offered
+                .iter()
+                .find(|o| o.eq_ignore_ascii_case(candidate))
+                .map(String::as_str)
+        })
+    };
+    let plain = || find(PLAIN_TYPES);
+    let html = || find(&[TEXT_HTML]);
+
+    match preference {
+        MimePreference::Plain => plain().or_else(html),
+        MimePreference::Html => html().or_else(plain),
+    }
+}
+
+/// Whether a MIME type is HTML and needs converting to text
+pub fn is_html(mime: &str) -> bool {
+    let mime = mime.to_ascii_lowercase();
+    mime == TEXT_HTML || mime.starts_with("text/html;")
+}
+
+/// Convert an HTML fragment to plain text
+///
+/// Block-level tags become line breaks, whitespace is collapsed the way a
+/// browser renders it, and `<script>`/`<style>` content is dropped.
+pub fn html_to_text(html: &str) -> String {
+    let mut out = String::new();
+    let mut skipping: Option<String> = None;
+    let mut chars = html.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '<' => {
+                let tag: String = chars.by_ref().take_while(|&c| c != '>').collect();
+                let closing = tag.starts_with('/');
+                let name = tag
+                    .trim_start_matches('/')
+                    .split(|c: char| c.is_whitespace() || c == '/')
+                    .next()
+                    .unwrap_or_default()
+                    .to_ascii_lowercase();
+
+                if let Some(ref skipped) = skipping {
+                    if closing && *skipped == name {
+                        skipping = None;
+                    }
+                    continue;
+                }
+
+                match name.as_str() {
+                    "script" | "style" | "head" if !closing => skipping = Some(name),
+                    "br" | "p" | "div" | "li" | "tr" | "h1" | "h2" | "h3" | "h4" | "h5" | "h6"
+                    | "blockquote" | "pre" => {
+                        while out.ends_with(' ') {
+                            out.pop();
+                        }
+                        if !out.is_empty() && !out.ends_with('\n') {
+                            out.push('\n');
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            _ if skipping.is_some() => {}
+            '&' => {
+                let rest = chars.as_str();
+                let entity = rest
+                    .find(';')
+                    .filter(|&end| end <= 10)
+                    .and_then(|end| Some((end, decode_entity(&rest[..end])?)));
+                match entity {
+                    Some((end, decoded)) => {
+                        out.push(decoded);
+                        chars = rest[end + 1..].chars();
+                    }
+                    None => out.push('&'),
+                }
+            }
+            c if c.is_whitespace() => {
+                if !out.is_empty() && !out.ends_with(char::is_whitespace) {
+                    out.push(' ');
+                }
+            }
+            c => out.push(c),
+        }
+    }
+
+    out.trim().to_string()
+}
+
+fn decode_entity(entity: &str) -> Option<char> {
+    match entity {
+        "amp" => Some('&'),
+        "lt" => Some('<'),
+        "gt" => Some('>'),
+        "quot" => Some('"'),
+        "apos" => Some('\''),
+        "nbsp" => Some(' '),
+        _ => {
+            let code = entity.strip_prefix('#')?;
+            let value = match code.strip_prefix(['x', 'X']) {
+                Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                None => code.parse().ok()?,
+            };
+            char::from_u32(value)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn offered(types: &[&str]) -> Vec<String> {
+        types.iter().map(|t| t.to_string()).collect()
+    }
+
+    #[test]
+    fn negotiation_follows_the_preference() {
+        let both = offered(&["text/html", "STRING", "UTF8_STRING", TEXT_PLAIN_UTF8]);
+        assert_eq!(
+            negotiate(&both, MimePreference::Plain),
+            Some(TEXT_PLAIN_UTF8)
+        );
+        assert_eq!(negotiate(&both, MimePreference::Html), Some("text/html"));
+
+        let x11 = offered(&["TARGETS", "STRING", "UTF8_STRING"]);
+        assert_eq!(negotiate(&x11, MimePreference::Plain), Some("UTF8_STRING"));
+    }
+
+    #[test]
+    fn negotiation_falls_back_to_what_is_offered() {
+        let html_only = offered(&["TEXT/HTML"]);
+        assert_eq!(
+            negotiate(&html_only, MimePreference::Plain),
+            Some("TEXT/HTML")
+        );
+        let plain_only = offered(&["UTF8_STRING"]);
+        assert_eq!(
+            negotiate(&plain_only, MimePreference::Html),
+            Some("UTF8_STRING")
+        );
+        assert_eq!(
+            negotiate(&offered(&["image/png"]), MimePreference::Plain),
+            None
+        );
+        assert!(is_html("text/html; charset=utf-8"));
+    }
+
+    #[test]
+    fn html_becomes_plain_text() {
+        assert_eq!(
+            html_to_text("<p>Hello  <b>world</b></p><div>Second&nbsp;line</div>"),
+            "Hello world\nSecond line"
+        );
+        assert_eq!(
+            html_to_text("a<br>b<br/>\n  c <li>item</li>"),
+            "a\nb\nc\nitem"
+        );
+        assert_eq!(
+            html_to_text(
+                "<head><title>x</title></head><style>p {}</style>Text<script>1 < 2</script>"
+            ),
+            "Text"
+        );
+    }
+
+    #[test]
+    fn entities_are_decoded() {
+        assert_eq!(
+            html_to_text("Fish &amp; chips &lt;b&gt; &quot;&#65;&#x42;&apos;"),
+            "Fish & chips <b> \"AB'"
+        );
+        // Unknown or unterminated entities are kept as they are
+        assert_eq!(html_to_text("AT&T &bogus; &amp"), "AT&T &bogus; &amp");
+    }
+}
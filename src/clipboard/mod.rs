@@ -0,0 +1,109 @@
+//! Clipboard and selection access
+//!
+//! Every clipboard read and write in the app goes through [`Clipboard`], which
+//! picks the backends available in the session (`wl-clipboard`, `xclip`,
+//! `xsel`, then `arboard`) and negotiates the MIME type of offered content.
+
+pub mod backend;
+pub mod mime;
+
+pub use backend::Backend;
+pub use mime::MimePreference;
+
+use crate::config::settings::ClipboardConfig;
+use serde::{Deserialize, Serialize};
+
+/// Selection buffer to read from or write to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Selection {
+    /// Highlighted text (middle-click paste)
+    Primary,
+    /// Explicitly copied text (Ctrl+C / Ctrl+V)
+    Clipboard,
+}
+
+impl std::fmt::Display for Selection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Selection::Primary => write!(f, "primary"),
+            Selection::Clipboard => write!(f, "clipboard"),
+        }
+    }
+}
+
+/// Clipboard access through the detected backends
+#[derive(Debug, Clone)]
+pub struct Clipboard {
+    backends: Vec<Backend>,
+    preference: MimePreference,
+}
+
+impl Clipboard {
+    /// Create a clipboard using the backends detected for this session
+    pub fn new(preference: MimePreference) -> Self {
+        Self {
+            backends: Backend::detect(),
+            preference,
+        }
+    }
+
+    /// Create a clipboard from the `[clipboard]` config section
+    pub fn from_config(config: &ClipboardConfig) -> Self {
+        Self::new(config.mime_preference)
+    }
+
+    /// Read text from a selection using the first backend that answers
+    pub fn read(&self, selection: Selection) -> Option<String> {
+        self.backends
+            .iter()
+            .find_map(|backend| backend.read(selection, self.preference))
+    }
+
+    /// Write text to a selection using the first backend that succeeds
+    pub fn write(&self, selection: Selection, text: &str) -> bool {
+        self.backends
+            .iter()
+            .any(|backend| backend.write(selection, text))
+    }
+
+    /// Write text to several selections, succeeding if any write succeeded
+    pub fn write_all(&self, selections: &[Selection], text: &str) -> bool {
+        let mut written = false;
+        for selection in selections {
+            written |= self.write(*selection, text);
+        }
+        written
+    }
+
+    /// Read the current contents of several selections, to put back with
+    /// [`Clipboard::restore`]
+    pub fn save_all(&self, selections: &[Selection]) -> Vec<(Selection, Option<String>)> {
+        selections
+            .iter()
+            .map(|&selection| (selection, self.read(selection)))
+            .collect()
+    }
+
+    /// Put back what [`Clipboard::save_all`] read, emptying selections that had nothing
+    pub fn restore(&self, saved: &[(Selection, Option<String>)]) {
+        for (selection, previous) in saved {
+            match previous {
+                Some(previous) => self.write(*selection, previous),
+                None => self.clear_all(&[*selection]),
+            };
+        }
+    }
+
+    /// Empty several selections, succeeding if any was cleared
+    pub fn clear_all(&self, selections: &[Selection]) -> bool {
+        let mut cleared = false;
+        for selection in selections {
+            cleared |= self
+                .backends
+                .iter()
+                .any(|backend| backend.clear(*selection));
+        }
+        cleared
+    }
+}
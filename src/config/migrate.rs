@@ -0,0 +1,97 @@
+//! Config schema versions and migrations
+//!
+//! Migrations run on the raw TOML table before it is deserialized, so they
+//! can rename or reshape keys that the current structs no longer accept.
+
+use toml::Table;
+
+/// Schema version written by this build
+pub const CURRENT_VERSION: u32 = 2;
+
+/// A migration upgrading a config table by one version
+type Migration = fn(&mut Table);
+
+/// Migrations indexed by the version they upgrade from
+const MIGRATIONS: [Migration; CURRENT_VERSION as usize] = [v0_to_v1, v1_to_v2];
+
+/// Version 0 files predate the `version` key and required every section.
+/// They already hold all v1 data; the missing sections take their defaults.
+fn v0_to_v1(_config: &mut Table) {}
+
+/// The old `ctrl+c` default for copying the result took Ctrl+C away from
+/// the text editors; saved files still carry it.
+fn v1_to_v2(config: &mut Table) {
+    let Some(bindings) = config
+        .get_mut("keybindings")
+        .and_then(|value| value.as_table_mut())
+    else {
+        return;
+    };
+    if bindings.get("copy_result").and_then(|value| value.as_str()) == Some("ctrl+c") {
+        bindings.insert("copy_result".to_string(), "ctrl+shift+c".into());
+    }
+}
+
+/// Get the schema version of a config table (0 if it has none)
+pub fn version_of(config: &Table) -> u32 {
+    config
+        .get("version")
+        .and_then(|v| v.as_integer())
+        .and_then(|v| u32::try_from(v).ok())
+        .unwrap_or(0)
+}
+
+/// Upgrade a config table to [`CURRENT_VERSION`]
+///
+/// Returns the version the table was migrated from, or `None` if it was
+/// already current. Tables from newer builds are left untouched.
+pub fn migrate(config: &mut Table) -> Option<u32> {
+    let version = version_of(config);
+    if version >= CURRENT_VERSION {
+        if version > CURRENT_VERSION {
+            tracing::warn!(
+                "Config version {} is newer than this build supports ({}); unknown settings are ignored",
+                version,
+                CURRENT_VERSION
+            );
+        }
+        return None;
+    }
+
+    for migration in &MIGRATIONS[version as usize..] {
+        migration(config);
+    }
+    config.insert("version".to_string(), i64::from(CURRENT_VERSION).into());
+    tracing::info!(
+        "Migrated config from version {} to {}",
+        version,
+        CURRENT_VERSION
+    );
+    Some(version)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn old_copy_binding_is_moved_off_ctrl_c() {
+        let mut config: Table = toml::from_str(
+            "version = 1\n[keybindings]\ncopy_result = \"ctrl+c\"\ncheck = \"ctrl+c\"\n",
+        )
+        .unwrap();
+        assert_eq!(migrate(&mut config), Some(1));
+        assert_eq!(version_of(&config), CURRENT_VERSION);
+        assert_eq!(
+            config["keybindings"]["copy_result"].as_str(),
+            Some("ctrl+shift+c")
+        );
+        // Other bindings are the user's choice
+        assert_eq!(config["keybindings"]["check"].as_str(), Some("ctrl+c"));
+
+        let mut custom: Table =
+            toml::from_str("version = 1\n[keybindings]\ncopy_result = \"alt+c\"\n").unwrap();
+        migrate(&mut custom);
+        assert_eq!(custom["keybindings"]["copy_result"].as_str(), Some("alt+c"));
+    }
+}
@@ -1,5 +1,14 @@
 //! Configuration management
 
+pub mod migrate;
+pub mod overrides;
+pub mod secrets;
 pub mod settings;
+pub mod validate;
 
-pub use settings::{config_path, load_config, save_config, AppConfig};
+pub use overrides::ConfigOverrides;
+pub use settings::{
+    config_path, load_config, presets_path, prompts_dir, save_config, set_config_path,
+    upgrade_config, AppConfig,
+};
+pub use validate::{load_checked, validate, CheckedConfig, ConfigIssue};
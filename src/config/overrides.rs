@@ -0,0 +1,197 @@
+//! Configuration layers above `config.toml`
+//!
+//! Values are applied in order: defaults, config file, the active profile,
+//! `AKKURATE_*` environment variables, then command-line flags. Overrides only affect the
+//! running process and are never written back to the config file.
+
+use super::settings::AppConfig;
+use std::path::PathBuf;
+
+/// Settings overriding the config file for this process
+#[derive(Debug, Clone, Default)]
+pub struct ConfigOverrides {
+    pub config_path: Option<PathBuf>,
+    pub profile: Option<String>,
+    pub gemini_key: Option<String>,
+    pub model: Option<String>,
+    pub preset: Option<String>,
+    pub ui_language: Option<String>,
+    pub theme: Option<String>,
+    /// Don't read or write the result cache
+    pub no_cache: bool,
+    /// Refuse all network requests
+    pub offline: bool,
+    /// `record:<dir>` or `replay:<dir>` for API responses
+    pub fixtures: Option<String>,
+}
+
+impl ConfigOverrides {
+    /// Read overrides from `AKKURATE_*` environment variables
+    ///
+    /// The API key is also read from `GEMINI_API_KEY`, with
+    /// `AKKURATE_GEMINI_API_KEY` taking precedence.
+    pub fn from_env() -> Self {
+        Self::from_vars(|name| std::env::var(name).ok())
+    }
+
+    /// Read overrides from variables looked up by `var`
+    fn from_vars(var: impl Fn(&str) -> Option<String>) -> Self {
+        // Empty values count as unset
+        let env = |name: &str| var(name).filter(|value| !value.is_empty());
+        let flag = |name: &str| env(name).is_some_and(|value| parse_flag(name, &value));
+        Self {
+            config_path: env("AKKURATE_CONFIG").map(PathBuf::from),
+            profile: env("AKKURATE_PROFILE"),
+            gemini_key: env("AKKURATE_GEMINI_API_KEY").or_else(|| env("GEMINI_API_KEY")),
+            model: env("AKKURATE_MODEL"),
+            preset: env("AKKURATE_PRESET"),
+            ui_language: env("AKKURATE_UI_LANG"),
+            theme: env("AKKURATE_THEME"),
+            no_cache: flag("AKKURATE_NO_CACHE"),
+            offline: flag("AKKURATE_OFFLINE"),
+            fixtures: env("AKKURATE_FIXTURES"),
+        }
+    }
+
+    /// Layer `other` on top of these overrides
+    pub fn merge(self, other: ConfigOverrides) -> Self {
+        Self {
+            config_path: other.config_path.or(self.config_path),
+            profile: other.profile.or(self.profile),
+            gemini_key: other.gemini_key.or(self.gemini_key),
+            model: other.model.or(self.model),
+            preset: other.preset.or(self.preset),
+            ui_language: other.ui_language.or(self.ui_language),
+            theme: other.theme.or(self.theme),
+            no_cache: other.no_cache || self.no_cache,
+            offline: other.offline || self.offline,
+            fixtures: other.fixtures.or(self.fixtures),
+        }
+    }
+
+    /// Apply the overrides to a configuration loaded from file
+    pub fn apply(&self, config: &mut AppConfig) {
+        if let Some(ref profile) = self.profile {
+            config.profile = profile.clone();
+        }
+        if !config.profile.is_empty() && !config.apply_profile() {
+            tracing::warn!("Unknown profile {:?}", config.profile);
+        }
+        if let Some(ref key) = self.gemini_key {
+            config.api.gemini_key = key.clone();
+        }
+        if let Some(ref model) = self.model {
+            config.api.model = model.clone();
+        }
+        if let Some(ref preset) = self.preset {
+            config.preferences.default_preset = preset.clone();
+        }
+        if let Some(ref language) = self.ui_language {
+            config.preferences.language = language.clone();
+        }
+        if let Some(ref theme) = self.theme {
+            config.preferences.theme = theme.clone();
+        }
+        if self.no_cache {
+            config.cache.enabled = false;
+        }
+        if self.offline {
+            config.network.offline = true;
+        }
+        if let Some(ref spec) = self.fixtures {
+            match crate::api::Fixtures::parse(spec) {
+                Ok(fixtures) => config.api.fixtures = Some(fixtures),
+                Err(e) => tracing::warn!("{:#}", e),
+            }
+        }
+    }
+
+    /// Get a copy of a configuration with the overrides applied
+    pub fn applied(&self, config: &AppConfig) -> AppConfig {
+        let mut config = config.clone();
+        self.apply(&mut config);
+        config
+    }
+}
+
+/// Read an on/off environment variable, treating unknown values as off
+fn parse_flag(name: &str, value: &str) -> bool {
+    match value.trim().to_ascii_lowercase().as_str() {
+        "1" | "true" | "yes" | "on" => true,
+        "0" | "false" | "no" | "off" | "" => false,
+        _ => {
+            tracing::warn!(
+                "Ignoring {}={:?}; expected 1/true/yes/on or 0/false/no/off",
+                name,
+                value
+            );
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn from(vars: &[(&str, &str)]) -> ConfigOverrides {
+        ConfigOverrides::from_vars(|name| {
+            vars.iter()
+                .find(|(n, _)| *n == name)
+                .map(|(_, value)| value.to_string())
+        })
+    }
+
+    #[test]
+    fn flags_accept_the_usual_spellings() {
+        for value in ["1", "true", "YES", "on"] {
+            assert!(from(&[("AKKURATE_OFFLINE", value)]).offline, "{}", value);
+        }
+        for value in ["0", "false", "No", "off", "", "maybe"] {
+            assert!(!from(&[("AKKURATE_OFFLINE", value)]).offline, "{}", value);
+            assert!(!from(&[("AKKURATE_NO_CACHE", value)]).no_cache, "{}", value);
+        }
+    }
+
+    #[test]
+    fn akkurate_key_wins_over_gemini_api_key() {
+        let generic = [("GEMINI_API_KEY", "generic")];
+        assert_eq!(from(&generic).gemini_key.as_deref(), Some("generic"));
+        let both = [
+            ("GEMINI_API_KEY", "generic"),
+            ("AKKURATE_GEMINI_API_KEY", "own"),
+        ];
+        assert_eq!(from(&both).gemini_key.as_deref(), Some("own"));
+        let empty = [
+            ("GEMINI_API_KEY", "generic"),
+            ("AKKURATE_GEMINI_API_KEY", ""),
+        ];
+        assert_eq!(from(&empty).gemini_key.as_deref(), Some("generic"));
+    }
+
+    #[test]
+    fn later_layers_win() {
+        let mut config: AppConfig =
+            toml::from_str("[api]\nmodel = \"file-model\"\n[preferences]\ntheme = \"dark\"\n")
+                .unwrap();
+        let defaults = AppConfig::default();
+        assert_ne!(config.api.model, defaults.api.model);
+
+        let env = from(&[
+            ("AKKURATE_MODEL", "env-model"),
+            ("AKKURATE_PRESET", "env-preset"),
+            ("AKKURATE_OFFLINE", "1"),
+        ]);
+        let cli = ConfigOverrides {
+            model: Some("cli-model".to_string()),
+            ..Default::default()
+        };
+        env.merge(cli).apply(&mut config);
+
+        assert_eq!(config.api.model, "cli-model");
+        assert_eq!(config.preferences.default_preset, "env-preset");
+        assert_eq!(config.preferences.theme, "dark");
+        assert_eq!(config.api.chunk_tokens, defaults.api.chunk_tokens);
+        assert!(config.network.offline);
+    }
+}
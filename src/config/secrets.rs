@@ -0,0 +1,58 @@
+//! API key storage in the system keyring
+//!
+//! Uses the freedesktop Secret Service (gnome-keyring, KWallet) on Linux.
+//! `config.toml` only holds a `keyring:<account>` reference to the secret.
+
+use anyhow::{Context, Result};
+
+/// Service name secrets are stored under
+const SERVICE: &str = "akkurate";
+
+/// Prefix marking a config value as a reference into the keyring
+const REFERENCE_PREFIX: &str = "keyring:";
+
+/// Keyring account holding the Gemini API key
+pub const GEMINI_ACCOUNT: &str = "gemini_key";
+
+/// Keyring account holding `network.client_cert_password`
+pub const CLIENT_CERT_ACCOUNT: &str = "client_cert_password";
+
+/// Keyring account holding the Gemini API key of a profile
+pub fn profile_account(profile: &str) -> String {
+    format!("{}.{}", GEMINI_ACCOUNT, profile)
+}
+
+/// Build the config value that refers to a keyring account
+pub fn reference(account: &str) -> String {
+    format!("{}{}", REFERENCE_PREFIX, account)
+}
+
+/// Get the keyring account a config value refers to, if it is a reference
+pub fn parse_reference(value: &str) -> Option<&str> {
+    value.strip_prefix(REFERENCE_PREFIX)
+}
+
+/// Store a secret in the keyring
+pub fn store(account: &str, secret: &str) -> Result<()> {
+    keyring::Entry::new(SERVICE, account)
+        .and_then(|entry| entry.set_password(secret))
+        .context("Failed to store secret in the system keyring")
+}
+
+/// Load a secret from the keyring
+pub fn load(account: &str) -> Result<String> {
+    keyring::Entry::new(SERVICE, account)
+        .and_then(|entry| entry.get_password())
+        .with_context(|| format!("Failed to read {:?} from the system keyring", account))
+}
+
+/// Remove a secret from the keyring, treating a missing entry as success
+///
+/// Returns whether there was a secret to remove.
+pub fn delete(account: &str) -> Result<bool> {
+    match keyring::Entry::new(SERVICE, account).and_then(|entry| entry.delete_credential()) {
+        Ok(()) => Ok(true),
+        Err(keyring::Error::NoEntry) => Ok(false),
+        Err(e) => Err(e).context("Failed to remove secret from the system keyring"),
+    }
+}
@@ -0,0 +1,176 @@
+//! Configuration validation
+//!
+//! Problems are collected and reported to the user instead of being replaced
+//! by defaults behind their back.
+
+use super::overrides::ConfigOverrides;
+use super::settings::{load_config, presets_path, prompts_dir, AppConfig, LANGUAGES, THEMES};
+use crate::core::{PresetManager, PromptTemplates};
+
+/// A problem found in the configuration
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigIssue {
+    /// Setting or file the problem is in, e.g. `preferences.theme`
+    pub location: String,
+    pub message: String,
+}
+
+impl ConfigIssue {
+    pub fn new(location: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            location: location.into(),
+            message: message.into(),
+        }
+    }
+}
+
+impl std::fmt::Display for ConfigIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.location, self.message)
+    }
+}
+
+/// Check a loaded configuration against the available presets
+pub fn validate(config: &AppConfig, presets: &PresetManager) -> Vec<ConfigIssue> {
+    let mut issues = Vec::new();
+    let prefs = &config.preferences;
+
+    if !config.profile.is_empty() && !config.profiles.contains_key(&config.profile) {
+        let available: Vec<&str> = config.profiles.keys().map(String::as_str).collect();
+        issues.push(ConfigIssue::new(
+            "profile",
+            format!(
+                "unknown profile {:?} (available: {})",
+                config.profile,
+                available.join(", ")
+            ),
+        ));
+    }
+
+    if presets.get(&prefs.default_preset).is_none() {
+        let available: Vec<&str> = presets.keys().into_iter().map(String::as_str).collect();
+        issues.push(ConfigIssue::new(
+            "preferences.default_preset",
+            format!(
+                "unknown preset {:?} (available: {})",
+                prefs.default_preset,
+                available.join(", ")
+            ),
+        ));
+    }
+
+    if !THEMES.contains(&prefs.theme.as_str()) {
+        issues.push(ConfigIssue::new(
+            "preferences.theme",
+            format!(
+                "invalid theme {:?} (expected one of: {})",
+                prefs.theme,
+                THEMES.join(", ")
+            ),
+        ));
+    }
+
+    if !LANGUAGES.contains(&prefs.language.as_str()) {
+        issues.push(ConfigIssue::new(
+            "preferences.language",
+            format!(
+                "invalid language {:?} (expected one of: {})",
+                prefs.language,
+                LANGUAGES.join(", ")
+            ),
+        ));
+    }
+
+    if config.api.model.trim().is_empty() {
+        issues.push(ConfigIssue::new("api.model", "model name is empty"));
+    }
+
+    if config.api.chunk_tokens == 0 {
+        issues.push(ConfigIssue::new("api.chunk_tokens", "must be at least 1"));
+    }
+
+    if config.api.max_parallel_requests == 0 {
+        issues.push(ConfigIssue::new(
+            "api.max_parallel_requests",
+            "must be at least 1",
+        ));
+    }
+
+    if let Err(e) = crate::api::http::build_client(&config.network) {
+        issues.push(ConfigIssue::new("network", format!("{:#}", e)));
+    }
+
+    let usage = &config.usage;
+    for (location, value) in [
+        ("usage.input_price", usage.input_price),
+        ("usage.output_price", usage.output_price),
+        ("usage.monthly_budget", usage.monthly_budget),
+    ] {
+        if value < 0.0 || value.is_nan() {
+            issues.push(ConfigIssue::new(location, "must not be negative"));
+        }
+    }
+    if usage.monthly_budget > 0.0 && usage.input_price == 0.0 && usage.output_price == 0.0 {
+        issues.push(ConfigIssue::new(
+            "usage.monthly_budget",
+            "set usage.input_price and usage.output_price for the budget to take effect",
+        ));
+    }
+
+    issues
+}
+
+/// Configuration and presets loaded with every problem collected
+pub struct CheckedConfig {
+    /// Configuration as stored on disk (defaults if it failed to load)
+    pub file_config: AppConfig,
+    /// Whether `config.toml` loaded; `false` means defaults are in use
+    pub file_loaded: bool,
+    /// File configuration with overrides applied
+    pub config: AppConfig,
+    pub presets: PresetManager,
+    pub templates: PromptTemplates,
+    pub issues: Vec<ConfigIssue>,
+}
+
+/// Load the config, presets and prompt templates, reporting problems instead of ignoring them
+pub fn load_checked(overrides: &ConfigOverrides) -> CheckedConfig {
+    let mut issues = Vec::new();
+
+    let (file_config, file_loaded) = match load_config() {
+        Ok(config) => (config, true),
+        Err(e) => {
+            issues.push(ConfigIssue::new("config.toml", format!("{:#}", e)));
+            (AppConfig::default(), false)
+        }
+    };
+    let config = overrides.applied(&file_config);
+
+    let mut presets = PresetManager::new();
+    if let Some(path) = presets_path() {
+        if let Err(e) = presets.load_custom_presets(&path) {
+            issues.push(ConfigIssue::new("presets.toml", format!("{:#}", e)));
+        }
+    }
+
+    let mut templates = PromptTemplates::new();
+    if let Some(dir) = prompts_dir() {
+        if let Err(e) = templates.load_overrides(&dir) {
+            issues.push(ConfigIssue::new("prompts", format!("{:#}", e)));
+        }
+    }
+
+    issues.extend(validate(&config, &presets));
+    for issue in &issues {
+        tracing::warn!("Config problem: {}", issue);
+    }
+
+    CheckedConfig {
+        file_config,
+        file_loaded,
+        config,
+        presets,
+        templates,
+        issues,
+    }
+}
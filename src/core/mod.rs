@@ -3,7 +3,16 @@
 pub mod checker;
 pub mod enhancer;
 pub mod presets;
+pub mod prompts;
+pub mod rules;
+pub mod segmenter;
 
-pub use checker::GrammarChecker;
+pub use checker::{
+    add_rule_issues, locate_issues, looks_like_correction, merge_results, GrammarChecker,
+    DEFAULT_CHUNK_TOKENS, DEFAULT_MAX_PARALLEL,
+};
 pub use enhancer::TextEnhancer;
-pub use presets::{PresetManager, StylePreset};
+pub use presets::{Dialect, ExampleRewrite, PresetManager, StylePreset, DEFAULT_ORDER};
+pub use prompts::{Prompt, PromptKind, PromptTemplate, PromptTemplates};
+pub use rules::{apply_fixes, check_offline, check_rules};
+pub use segmenter::{estimate_tokens, segment, Segment};
@@ -2,37 +2,138 @@
 
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashSet};
 use std::path::Path;
 
+/// Position of presets without an explicit `order`, after the built-in ones
+pub const DEFAULT_ORDER: i32 = 100;
+
 /// A style preset for text enhancement
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct StylePreset {
     pub name: String,
+    /// Names by UI language (`chinese`, `english`), falling back to `name`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub names: BTreeMap<String, String>,
+    /// Short explanation shown next to the preset picker
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Emoji or symbol shown before the name
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub icon: Option<String>,
+    /// Position in pickers and lists; lower comes first, ties are sorted by key
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub order: Option<i32>,
+    /// Leave out of pickers and shortcuts; still usable with `--preset`
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub hidden: bool,
     pub tone: String,
     pub formality: String,
     pub instructions: String,
+    /// Who the text is written for, e.g. "customers" or "reviewers"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub audience: Option<String>,
+    /// Desired length, e.g. "under 100 words" or "about the same as the original"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target_length: Option<String>,
+    /// English spelling and usage to follow
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dialect: Option<Dialect>,
+    /// Words the rewrite must not use
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub banned_words: Vec<String>,
+    /// Sample rewrites showing the desired style
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub examples: Vec<ExampleRewrite>,
+}
+
+impl StylePreset {
+    /// Name in the given UI language (a `preferences.language` value)
+    pub fn localized_name(&self, language: &str) -> &str {
+        self.names.get(language).unwrap_or(&self.name)
+    }
+
+    /// Name with the icon in front, if there is one
+    pub fn label(&self, language: &str) -> String {
+        match self.icon {
+            Some(ref icon) => format!("{} {}", icon, self.localized_name(language)),
+            None => self.localized_name(language).to_string(),
+        }
+    }
+}
+
+/// Variety of English a preset writes in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Dialect {
+    #[serde(alias = "US")]
+    Us,
+    #[serde(alias = "UK")]
+    Uk,
+}
+
+impl Dialect {
+    pub fn all() -> &'static [Dialect] {
+        &[Dialect::Us, Dialect::Uk]
+    }
+
+    /// Instruction for the model
+    pub fn description(&self) -> &'static str {
+        match self {
+            Dialect::Us => "American English spelling and usage",
+            Dialect::Uk => "British English spelling and usage",
+        }
+    }
+}
+
+impl std::fmt::Display for Dialect {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Dialect::Us => write!(f, "US"),
+            Dialect::Uk => write!(f, "UK"),
+        }
+    }
+}
+
+/// An example of text before and after applying a preset
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExampleRewrite {
+    pub before: String,
+    pub after: String,
+}
+
+/// Contents of `presets.toml`
+///
+/// Read as raw tables, since overrides of built-in presets may leave fields out.
+#[derive(Serialize, Deserialize)]
+struct PresetsFile<P = StylePreset> {
+    presets: BTreeMap<String, P>,
 }
 
 /// Manages style presets
 pub struct PresetManager {
-    presets: HashMap<String, StylePreset>,
+    presets: BTreeMap<String, StylePreset>,
+    /// Keys of presets from `presets.toml` or added at runtime
+    custom: HashSet<String>,
 }
 
 impl PresetManager {
     /// Create a new preset manager with default presets
     pub fn new() -> Self {
-        let mut presets = HashMap::new();
+        let mut presets = BTreeMap::new();
 
         presets.insert(
             "casual".to_string(),
             StylePreset {
                 name: "Casual".to_string(),
+                names: localized("日常", "Casual"),
+                order: Some(10),
                 tone: "friendly, conversational".to_string(),
                 formality: "informal".to_string(),
                 instructions:
                     "Use simple words, contractions are okay, keep it natural and relaxed"
                         .to_string(),
+                ..Default::default()
             },
         );
 
@@ -40,10 +141,13 @@ impl PresetManager {
             "business".to_string(),
             StylePreset {
                 name: "Business".to_string(),
+                names: localized("商务", "Business"),
+                order: Some(20),
                 tone: "professional, polite".to_string(),
                 formality: "formal".to_string(),
                 instructions: "Clear and concise, avoid slang, maintain professional courtesy"
                     .to_string(),
+                ..Default::default()
             },
         );
 
@@ -51,11 +155,14 @@ impl PresetManager {
             "academic".to_string(),
             StylePreset {
                 name: "Academic".to_string(),
+                names: localized("学术", "Academic"),
+                order: Some(30),
                 tone: "objective, analytical".to_string(),
                 formality: "highly formal".to_string(),
                 instructions:
                     "Use precise terminology, passive voice acceptable, maintain scholarly tone"
                         .to_string(),
+                ..Default::default()
             },
         );
 
@@ -63,14 +170,20 @@ impl PresetManager {
             "creative".to_string(),
             StylePreset {
                 name: "Creative".to_string(),
+                names: localized("创意", "Creative"),
+                order: Some(40),
                 tone: "expressive, vivid".to_string(),
                 formality: "flexible".to_string(),
                 instructions: "Encourage creativity, use varied sentence structures, be engaging"
                     .to_string(),
+                ..Default::default()
             },
         );
 
-        Self { presets }
+        Self {
+            presets,
+            custom: HashSet::new(),
+        }
     }
 
     /// Load custom presets from a TOML file
@@ -81,15 +194,65 @@ impl PresetManager {
 
         let content = std::fs::read_to_string(path).context("Failed to read presets file")?;
 
-        #[derive(Deserialize)]
-        struct PresetsFile {
-            presets: HashMap<String, StylePreset>,
+        let file: PresetsFile<toml::Table> =
+            toml::from_str(&content).context("Failed to parse presets file")?;
+
+        let builtins = Self::new().presets;
+        for (key, fields) in file.presets {
+            let preset = match builtins.get(&key) {
+                Some(builtin) => override_builtin(builtin, fields),
+                None => toml::Value::Table(fields).try_into().map_err(Into::into),
+            }
+            .with_context(|| format!("Invalid preset {:?}", key))?;
+            self.add(key, preset);
+        }
+        Ok(())
+    }
+
+    /// Write the custom presets to a TOML file
+    ///
+    /// Built-in presets are only written when they have been overridden.
+    pub fn save_custom_presets(&self, path: &Path) -> Result<()> {
+        let file = PresetsFile {
+            presets: self
+                .custom
+                .iter()
+                .filter_map(|key| Some((key.clone(), self.presets.get(key)?.clone())))
+                .collect(),
+        };
+        let content = toml::to_string_pretty(&file).context("Failed to serialize presets")?;
+
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).context("Failed to create config directory")?;
         }
+        crate::config::settings::write_private(path, &content)
+            .context("Failed to write presets file")
+    }
 
-        let file: PresetsFile = toml::from_str(&content).context("Failed to parse presets file")?;
+    /// Add a custom preset, replacing any preset with the same key
+    pub fn add(&mut self, key: String, preset: StylePreset) {
+        self.custom.insert(key.clone());
+        self.presets.insert(key, preset);
+    }
 
-        self.presets.extend(file.presets);
-        Ok(())
+    /// Remove a custom preset
+    ///
+    /// A removed override of a built-in preset brings the built-in back.
+    /// Built-in presets themselves can't be removed and yield `None`.
+    pub fn remove(&mut self, key: &str) -> Option<StylePreset> {
+        if !self.custom.remove(key) {
+            return None;
+        }
+        let removed = self.presets.remove(key);
+        if let Some(builtin) = Self::new().presets.remove(key) {
+            self.presets.insert(key.to_string(), builtin);
+        }
+        removed
+    }
+
+    /// Whether a preset comes from `presets.toml` (or overrides a built-in)
+    pub fn is_custom(&self, key: &str) -> bool {
+        self.custom.contains(key)
     }
 
     /// Get a preset by key
@@ -97,19 +260,95 @@ impl PresetManager {
         self.presets.get(key)
     }
 
-    /// Get all preset keys
+    /// Get all preset keys in display order
     pub fn keys(&self) -> Vec<&String> {
-        self.presets.keys().collect()
+        self.all().into_iter().map(|(key, _)| key).collect()
     }
 
-    /// Get all presets as a vector of (key, preset) pairs
+    /// Get the keys of presets shown in pickers, in display order
+    pub fn visible_keys(&self) -> Vec<&String> {
+        self.all()
+            .into_iter()
+            .filter(|(_, preset)| !preset.hidden)
+            .map(|(key, _)| key)
+            .collect()
+    }
+
+    /// Get all presets as (key, preset) pairs in display order
     pub fn all(&self) -> Vec<(&String, &StylePreset)> {
-        self.presets.iter().collect()
+        let mut all: Vec<_> = self.presets.iter().collect();
+        // Stable sort: the map is already ordered by key
+        all.sort_by_key(|(_, preset)| preset.order.unwrap_or(DEFAULT_ORDER));
+        all
     }
 }
 
+/// Apply the fields set in `presets.toml` to a built-in preset
+///
+/// Fields left out, such as `order` and the localized names, keep their
+/// built-in values. A new `name` drops the built-in localized names.
+fn override_builtin(builtin: &StylePreset, fields: toml::Table) -> Result<StylePreset> {
+    let mut table = toml::Table::try_from(builtin)?;
+    if fields.contains_key("name") && !fields.contains_key("names") {
+        table.remove("names");
+    }
+    table.extend(fields);
+    Ok(toml::Value::Table(table).try_into()?)
+}
+
+/// Names of a built-in preset in Chinese and English
+fn localized(chinese: &str, english: &str) -> BTreeMap<String, String> {
+    BTreeMap::from([
+        ("chinese".to_string(), chinese.to_string()),
+        ("english".to_string(), english.to_string()),
+    ])
+}
+
 impl Default for PresetManager {
     fn default() -> Self {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn load(name: &str, content: &str) -> Result<PresetManager> {
+        let path = std::env::temp_dir().join(format!(
+            "akkurate-presets-{}-{}.toml",
+            name,
+            std::process::id()
+        ));
+        std::fs::write(&path, content).unwrap();
+        let mut manager = PresetManager::new();
+        let result = manager.load_custom_presets(&path);
+        let _ = std::fs::remove_file(&path);
+        result.map(|()| manager)
+    }
+
+    #[test]
+    fn overrides_of_builtins_keep_the_fields_they_leave_out() {
+        let manager = load(
+            "override",
+            "[presets.casual]\ninstructions = \"Keep it short\"\n",
+        )
+        .unwrap();
+        let casual = manager.get("casual").unwrap();
+        assert_eq!(casual.instructions, "Keep it short");
+        assert_eq!(casual.order, Some(10));
+        assert_eq!(casual.localized_name("chinese"), "日常");
+        assert_eq!(casual.tone, "friendly, conversational");
+
+        let renamed = load("rename", "[presets.casual]\nname = \"Chill\"\n").unwrap();
+        assert_eq!(
+            renamed.get("casual").unwrap().localized_name("chinese"),
+            "Chill"
+        );
+    }
+
+    #[test]
+    fn new_presets_need_all_required_fields() {
+        assert!(load("new", "[presets.terse]\ninstructions = \"Short\"\n").is_err());
+    }
+}
@@ -0,0 +1,483 @@
+//! Built-in checks for common mistakes that need no model
+//!
+//! These run instantly and offline: repeated words, double spaces, a/an,
+//! unmatched brackets and quotes, and a few confusables such as its/it's.
+//! They err on the side of staying quiet; anything ambiguous is left to the
+//! model.
+
+use crate::api::{CheckResult, GrammarIssue};
+
+/// Words that are often correctly doubled ("that that", "had had")
+const DOUBLING_ALLOWED: &[&str] = &["had", "that", "bye", "ha", "no"];
+
+/// Words after "its" that show it should be "it's"
+///
+/// Only words that can't follow a possessive; "its being sold", "its going
+/// rate" and "its really long tail" are fine.
+const AFTER_IT_IS: &[&str] = &["a", "an", "the", "not", "been", "gonna"];
+
+/// Modal verbs often followed by "of" instead of "have"
+const MODALS: &[&str] = &["could", "would", "should", "must", "might"];
+
+/// Starts of words spelled with a vowel but said with a consonant sound
+const CONSONANT_SOUND: &[&str] = &[
+    "eu", "ewe", "one", "once", "ubiq", "ukr", "unan", "unic", "unif", "unio", "uniq", "unis",
+    "unit", "univ", "unix", "ura", "uri", "uro", "usa", "use", "usi", "usu", "uten", "uti", "utop",
+];
+
+/// Starts of words said with a vowel sound despite their spelling, checked
+/// before `CONSONANT_SOUND`
+const VOWEL_SOUND: &[&str] = &[
+    "euler", "heir", "herb", "honest", "honor", "honour", "hour", "onero",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Rule {
+    RepeatedWord,
+    DoubleSpace,
+    Article,
+    UnmatchedBracket,
+    UnmatchedQuote,
+    ItsItIs,
+    ModalOf,
+}
+
+impl Rule {
+    fn name(self) -> &'static str {
+        match self {
+            Rule::RepeatedWord => "repeated word",
+            Rule::DoubleSpace => "extra space",
+            Rule::Article => "a/an",
+            Rule::UnmatchedBracket => "unmatched bracket",
+            Rule::UnmatchedQuote => "unmatched quote",
+            Rule::ItsItIs => "its/it's",
+            Rule::ModalOf => "could have",
+        }
+    }
+
+    fn explanation(self, chinese: bool) -> &'static str {
+        match (self, chinese) {
+            (Rule::RepeatedWord, false) => "The word is repeated.",
+            (Rule::RepeatedWord, true) => "单词重复。",
+            (Rule::DoubleSpace, false) => "There are two spaces between words.",
+            (Rule::DoubleSpace, true) => "单词之间有两个空格。",
+            (Rule::Article, false) => {
+                "Use \"an\" before a vowel sound and \"a\" before a consonant sound."
+            }
+            (Rule::Article, true) => "元音开头的词前用 \"an\"，辅音开头的词前用 \"a\"。",
+            (Rule::UnmatchedBracket, false) => "This bracket has no matching partner.",
+            (Rule::UnmatchedBracket, true) => "括号没有配对。",
+            (Rule::UnmatchedQuote, false) => "This quotation mark is never closed.",
+            (Rule::UnmatchedQuote, true) => "引号没有闭合。",
+            (Rule::ItsItIs, false) => "\"it's\" means \"it is\"; \"its\" is possessive.",
+            (Rule::ItsItIs, true) => "\"it's\" 是 \"it is\" 的缩写，\"its\" 表示所属。",
+            (Rule::ModalOf, false) => "\"of\" after a modal verb should be \"have\".",
+            (Rule::ModalOf, true) => "情态动词后应使用 \"have\" 而不是 \"of\"。",
+        }
+    }
+}
+
+/// A word and where it is in the text
+#[derive(Debug, Clone, Copy)]
+struct Word<'a> {
+    start: usize,
+    text: &'a str,
+}
+
+impl Word<'_> {
+    fn end(&self) -> usize {
+        self.start + self.text.len()
+    }
+
+    fn is(&self, word: &str) -> bool {
+        self.text.eq_ignore_ascii_case(word)
+    }
+}
+
+/// Check `text` with the built-in rules
+///
+/// Explanations are written in Chinese if `chinese` is set, otherwise in
+/// English. Every issue has its offset set; an issue whose correction equals
+/// the original only points something out and has no fix.
+pub fn check_rules(text: &str, chinese: bool) -> Vec<GrammarIssue> {
+    let words = words(text);
+    let mut found = Vec::new();
+    repeated_words(text, &words, &mut found);
+    double_spaces(text, &mut found);
+    articles(text, &words, &mut found);
+    confusables(text, &words, &mut found);
+    brackets(text, &mut found);
+
+    // Overlapping fixes can't both be applied; keep the one found first
+    found.sort_by_key(|(offset, _, _, _)| *offset);
+    let mut issues: Vec<GrammarIssue> = Vec::new();
+    let mut fixed_until = 0;
+    for (offset, original, corrected, rule) in found {
+        let has_fix = original != corrected;
+        if has_fix {
+            if offset < fixed_until {
+                continue;
+            }
+            fixed_until = offset + original.len();
+        }
+        issues.push(GrammarIssue {
+            original: original.to_string(),
+            corrected,
+            explanation: rule.explanation(chinese).to_string(),
+            rule: rule.name().to_string(),
+            offset: Some(offset),
+        });
+    }
+    issues
+}
+
+/// Check `text` with the built-in rules only, applying their fixes
+pub fn check_offline(text: &str, chinese: bool) -> CheckResult {
+    let issues = check_rules(text, chinese);
+    CheckResult {
+        corrected_text: apply_fixes(text, &issues),
+        issues,
+        summary: None,
+        unchecked: Vec::new(),
+    }
+}
+
+/// Apply the fixes of issues found in `text`
+///
+/// Issues without a fix, without an offset or overlapping an earlier fix are
+/// skipped.
+pub fn apply_fixes(text: &str, issues: &[GrammarIssue]) -> String {
+    let mut fixes: Vec<(usize, &GrammarIssue)> = issues
+        .iter()
+        .filter(|issue| issue.original != issue.corrected)
+        .filter_map(|issue| Some((issue.offset?, issue)))
+        .filter(|(offset, issue)| {
+            text.get(*offset..)
+                .is_some_and(|rest| rest.starts_with(&issue.original))
+        })
+        .collect();
+    fixes.sort_by_key(|(offset, _)| *offset);
+
+    let mut fixed = String::with_capacity(text.len());
+    let mut cursor = 0;
+    for (offset, issue) in fixes {
+        if offset < cursor {
+            continue;
+        }
+        fixed.push_str(&text[cursor..offset]);
+        fixed.push_str(&issue.corrected);
+        cursor = offset + issue.original.len();
+    }
+    fixed.push_str(&text[cursor..]);
+    fixed
+}
+
+type Found<'a> = Vec<(usize, &'a str, String, Rule)>;
+
+/// Words in the text, including apostrophes inside them ("it's")
+fn words(text: &str) -> Vec<Word<'_>> {
+    let mut words = Vec::new();
+    let mut start = None;
+    let mut chars = text.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        let next_is_alphanumeric = chars.peek().is_some_and(|(_, n)| n.is_alphanumeric());
+        let in_word =
+            c.is_alphanumeric() || (start.is_some() && is_apostrophe(c) && next_is_alphanumeric);
+        match (in_word, start) {
+            (true, None) => start = Some(i),
+            (false, Some(s)) => {
+                words.push(Word {
+                    start: s,
+                    text: &text[s..i],
+                });
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(s) = start {
+        words.push(Word {
+            start: s,
+            text: &text[s..],
+        });
+    }
+    words
+}
+
+fn is_apostrophe(c: char) -> bool {
+    c == '\'' || c == '’'
+}
+
+/// Whether only spaces (and at most a line break) separate two words
+fn adjacent(text: &str, first: &Word, second: &Word) -> bool {
+    let gap = &text[first.end()..second.start];
+    !gap.is_empty() && gap.chars().all(char::is_whitespace) && gap.matches('\n').count() <= 1
+}
+
+fn repeated_words<'a>(text: &'a str, words: &[Word<'a>], found: &mut Found<'a>) {
+    for pair in words.windows(2) {
+        let (first, second) = (pair[0], pair[1]);
+        let lower = first.text.to_lowercase();
+        if first.text.eq_ignore_ascii_case(second.text)
+            && adjacent(text, &first, &second)
+            && !lower.chars().all(|c| c.is_ascii_digit())
+            && !DOUBLING_ALLOWED.contains(&lower.as_str())
+        {
+            found.push((
+                first.start,
+                &text[first.start..second.end()],
+                first.text.to_string(),
+                Rule::RepeatedWord,
+            ));
+        }
+    }
+}
+
+/// Exactly two spaces between words; longer runs are usually alignment, and
+/// two spaces after a sentence are a common typing style
+fn double_spaces<'a>(text: &'a str, found: &mut Found<'a>) {
+    let bytes = text.as_bytes();
+    let mut i = 0;
+    while let Some(pos) = text[i..].find("  ").map(|p| p + i) {
+        let end = pos + 2;
+        let between_words = pos > 0
+            && !bytes[pos - 1].is_ascii_whitespace()
+            && !matches!(bytes[pos - 1], b'.' | b'!' | b'?')
+            && bytes.get(end).is_some_and(|b| !b.is_ascii_whitespace());
+        if between_words {
+            found.push((pos, &text[pos..end], " ".to_string(), Rule::DoubleSpace));
+        }
+        i = text[end..]
+            .find(|c| c != ' ')
+            .map_or(text.len(), |p| p + end);
+    }
+}
+
+fn articles<'a>(text: &'a str, words: &[Word<'a>], found: &mut Found<'a>) {
+    for pair in words.windows(2) {
+        let (article, next) = (pair[0], pair[1]);
+        let wants_an = match article.text {
+            "a" | "an" => article.text == "an",
+            // A capital "A" is often a name ("plan A"); only trust it at the start of a sentence
+            "A" | "An" if starts_sentence(text, article.start) => article.text == "An",
+            _ => continue,
+        };
+        if !adjacent(text, &article, &next) || text[article.end()..next.start].contains('\n') {
+            continue;
+        }
+        // Skip letters, numbers and acronyms, which are read out differently
+        let first = next.text.chars().next().unwrap_or_default();
+        if next.text.chars().count() < 2
+            || !first.is_alphabetic()
+            || next.text.chars().all(|c| c.is_uppercase())
+        {
+            continue;
+        }
+
+        let needs_an = match vowel_sound(next.text) {
+            Some(vowel) => vowel,
+            None => continue,
+        };
+        if needs_an != wants_an {
+            let replacement = match (needs_an, article.text.starts_with('A')) {
+                (true, true) => "An",
+                (true, false) => "an",
+                (false, true) => "A",
+                (false, false) => "a",
+            };
+            found.push((
+                article.start,
+                &text[article.start..next.end()],
+                format!("{}{}", replacement, &text[article.end()..next.end()]),
+                Rule::Article,
+            ));
+        }
+    }
+}
+
+/// Whether a word starts with a vowel sound, if that can be told from its spelling
+fn vowel_sound(word: &str) -> Option<bool> {
+    let lower = word.to_lowercase();
+    if VOWEL_SOUND.iter().any(|start| lower.starts_with(start)) {
+        return Some(true);
+    }
+    if CONSONANT_SOUND.iter().any(|start| lower.starts_with(start)) {
+        return Some(false);
+    }
+    let first = lower.chars().next()?;
+    if !first.is_ascii_alphabetic() {
+        return None;
+    }
+    // "h" is silent too often ("historic", "hotel") to correct "an h..."
+    if first == 'h' {
+        return None;
+    }
+    Some(matches!(first, 'a' | 'e' | 'i' | 'o' | 'u'))
+}
+
+/// Whether `offset` is at the start of the text, a line or a sentence
+fn starts_sentence(text: &str, offset: usize) -> bool {
+    let before = text[..offset].trim_end_matches([' ', '\t', '"', '“', '(']);
+    before.is_empty() || before.ends_with(['.', '!', '?', '\n'])
+}
+
+fn confusables<'a>(text: &'a str, words: &[Word<'a>], found: &mut Found<'a>) {
+    for (i, pair) in words.windows(2).enumerate() {
+        let (first, second) = (pair[0], pair[1]);
+        if !adjacent(text, &first, &second) {
+            continue;
+        }
+        let original = &text[first.start..second.end()];
+        let gap = &text[first.end()..second.start];
+
+        if first.is("its") && AFTER_IT_IS.iter().any(|w| second.is(w)) {
+            let it = &first.text[..2];
+            let corrected = format!("{}'s{}{}", it, gap, second.text);
+            found.push((first.start, original, corrected, Rule::ItsItIs));
+        } else if (first.is("it's") || first.is("it’s")) && second.is("own") {
+            let corrected = format!("{}s{}{}", &first.text[..2], gap, second.text);
+            found.push((first.start, original, corrected, Rule::ItsItIs));
+        } else if MODALS.iter().any(|m| first.is(m))
+            && second.text == "of"
+            // "could of course" is fine
+            && !words.get(i + 2).is_some_and(|w| w.is("course"))
+        {
+            let corrected = format!("{}{}have", first.text, gap);
+            found.push((first.start, original, corrected, Rule::ModalOf));
+        }
+    }
+}
+
+fn brackets<'a>(text: &'a str, found: &mut Found<'a>) {
+    let mut open: Vec<(usize, char)> = Vec::new();
+    let mut straight_quotes = Vec::new();
+    let flag = |offset: usize, c: char, rule: Rule, found: &mut Found<'a>| {
+        let original = &text[offset..offset + c.len_utf8()];
+        found.push((offset, original, original.to_string(), rule));
+    };
+
+    for (i, c) in text.char_indices() {
+        match c {
+            '(' | '[' | '{' | '“' => open.push((i, c)),
+            ')' | ']' | '}' | '”' => {
+                let opener = match c {
+                    ')' => '(',
+                    ']' => '[',
+                    '}' => '{',
+                    _ => '“',
+                };
+                if open.last().is_some_and(|(_, o)| *o == opener) {
+                    open.pop();
+                } else if !is_list_marker_or_smiley(text, i) {
+                    let rule = if c == '”' {
+                        Rule::UnmatchedQuote
+                    } else {
+                        Rule::UnmatchedBracket
+                    };
+                    flag(i, c, rule, found);
+                }
+            }
+            '"' => straight_quotes.push(i),
+            _ => {}
+        }
+    }
+
+    for (i, c) in open {
+        let rule = if c == '“' {
+            Rule::UnmatchedQuote
+        } else {
+            Rule::UnmatchedBracket
+        };
+        flag(i, c, rule, found);
+    }
+    if straight_quotes.len() % 2 == 1 {
+        flag(
+            *straight_quotes.last().unwrap(),
+            '"',
+            Rule::UnmatchedQuote,
+            found,
+        );
+    }
+}
+
+/// Closing brackets in "1)" or "a)" list markers and ":)" smileys
+fn is_list_marker_or_smiley(text: &str, offset: usize) -> bool {
+    let line = &text[text[..offset].rfind('\n').map_or(0, |p| p + 1)..offset];
+    let marker = line.trim_start();
+    (!marker.is_empty() && marker.chars().count() <= 2 && marker.chars().all(char::is_alphanumeric))
+        || line.ends_with([':', ';'])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixed(text: &str) -> String {
+        check_offline(text, false).corrected_text
+    }
+
+    fn rules(text: &str) -> Vec<String> {
+        check_rules(text, false)
+            .into_iter()
+            .map(|issue| issue.rule)
+            .collect()
+    }
+
+    #[test]
+    fn fixes_common_mistakes() {
+        assert_eq!(fixed("I saw the the cat."), "I saw the cat.");
+        assert_eq!(fixed("One  space is enough."), "One space is enough.");
+        assert_eq!(
+            fixed("I ate a apple and an banana."),
+            "I ate an apple and a banana."
+        );
+        assert_eq!(fixed("An university. A hour."), "A university. An hour.");
+        assert_eq!(
+            fixed("Its a trap, it's own fault."),
+            "It's a trap, its own fault."
+        );
+        assert_eq!(fixed("You could of asked."), "You could have asked.");
+    }
+
+    #[test]
+    fn leaves_correct_text_alone() {
+        for text in [
+            "He had had enough, and that that was true surprised us.",
+            "An honest answer from a European union.",
+            "Plan A is an option; an FBI agent and a 5 were there.",
+            "Its tail wags, and you could of course leave.",
+            "Columns:    aligned    like    this.\n  Indented line.",
+            "1) first\n2) second :) \"quoted\" (fine) [too] “curly”",
+            "An onerous task: an Eulerian path, a unanimous vote and a Ukrainian city.",
+            "A utopia runs on a Unix system.  Its being sold at its going rate.",
+            "Its really long tail is too short for its getting around.",
+        ] {
+            assert_eq!(check_rules(text, false).len(), 0, "{}", text);
+            assert_eq!(fixed(text), text);
+        }
+    }
+
+    #[test]
+    fn points_out_unmatched_brackets_and_quotes() {
+        assert_eq!(rules("A (note without end."), ["unmatched bracket"]);
+        assert_eq!(rules("Extra ] here."), ["unmatched bracket"]);
+        assert_eq!(rules("He said \"hi and left."), ["unmatched quote"]);
+        assert_eq!(rules("“Open"), ["unmatched quote"]);
+
+        let issues = check_rules("x (y", false);
+        assert_eq!(issues[0].offset, Some(2));
+        assert_eq!(fixed("x (y"), "x (y");
+    }
+
+    #[test]
+    fn offsets_point_at_the_original() {
+        let text = "Ünïcode: the the end,  then a apple";
+        let issues = check_rules(text, true);
+        assert_eq!(issues.len(), 3);
+        for issue in &issues {
+            let offset = issue.offset.unwrap();
+            assert_eq!(&text[offset..offset + issue.original.len()], issue.original);
+        }
+        assert_eq!(issues[0].explanation, "单词重复。");
+    }
+}
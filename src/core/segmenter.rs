@@ -0,0 +1,273 @@
+//! Splitting long texts into pieces that can be checked separately
+//!
+//! Texts are split on paragraph boundaries first, then on sentences, then on
+//! words, so each segment stays under a token budget while reading naturally.
+
+use std::ops::Range;
+
+/// Rough number of characters per token for English text
+const CHARS_PER_TOKEN: usize = 4;
+
+/// Size of a character in fractions of a token (`1 / CHARS_PER_TOKEN`)
+///
+/// CJK and most other non-ASCII characters take about a token each, so
+/// counting them like English letters would underestimate Chinese text
+/// fourfold.
+fn char_weight(c: char) -> usize {
+    if c.is_ascii() {
+        1
+    } else {
+        CHARS_PER_TOKEN
+    }
+}
+
+fn weight(text: &str) -> usize {
+    text.chars().map(char_weight).sum()
+}
+
+/// A part of the input text
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Segment<'a> {
+    /// Byte offset of the segment in the whole text
+    pub offset: usize,
+    /// The segment, without surrounding whitespace
+    pub text: &'a str,
+}
+
+impl Segment<'_> {
+    /// Byte offset just past the segment
+    pub fn end(&self) -> usize {
+        self.offset + self.text.len()
+    }
+}
+
+/// Estimate how many tokens a text takes up
+pub fn estimate_tokens(text: &str) -> usize {
+    weight(text).div_ceil(CHARS_PER_TOKEN)
+}
+
+/// Split a text into segments of at most `max_tokens` (estimated) tokens
+///
+/// Whitespace between segments is left out; everything else is covered by
+/// exactly one segment, in order.
+pub fn segment(text: &str, max_tokens: usize) -> Vec<Segment<'_>> {
+    let max_weight = max_tokens.max(1) * CHARS_PER_TOKEN;
+
+    let mut pieces = Vec::new();
+    split_pieces(text, 0..text.len(), 0, max_weight, &mut pieces);
+
+    // Pack neighbouring pieces back together as long as they fit
+    let mut ranges: Vec<Range<usize>> = Vec::new();
+    let mut current: Option<(Range<usize>, usize)> = None;
+    for piece in pieces {
+        let size = weight(&text[piece.clone()]);
+        current = match current {
+            Some((range, total)) if total + size <= max_weight => {
+                Some((range.start..piece.end, total + size))
+            }
+            Some((range, _)) => {
+                ranges.push(range);
+                Some((piece, size))
+            }
+            None => Some((piece, size)),
+        };
+    }
+    ranges.extend(current.map(|(range, _)| range));
+
+    ranges
+        .into_iter()
+        .filter_map(|range| {
+            let raw = &text[range.clone()];
+            let trimmed = raw.trim();
+            (!trimmed.is_empty()).then(|| Segment {
+                offset: range.start + (raw.len() - raw.trim_start().len()),
+                text: trimmed,
+            })
+        })
+        .collect()
+}
+
+/// Split `range` of `text` into pieces weighing at most `max_weight`,
+/// using the boundaries of `level` and finer levels where needed
+fn split_pieces(
+    text: &str,
+    range: Range<usize>,
+    level: usize,
+    max_weight: usize,
+    out: &mut Vec<Range<usize>>,
+) {
+    let part = &text[range.clone()];
+    if weight(part) <= max_weight {
+        out.push(range);
+        return;
+    }
+
+    let ends = match level {
+        0 => paragraph_ends(part),
+        1 => sentence_ends(part),
+        2 => word_ends(part),
+        _ => weight_ends(part, max_weight),
+    };
+
+    let mut start = 0;
+    for end in ends.into_iter().chain(std::iter::once(part.len())) {
+        if end > start {
+            split_pieces(
+                text,
+                range.start + start..range.start + end,
+                level + 1,
+                max_weight,
+                out,
+            );
+            start = end;
+        }
+    }
+}
+
+/// Offsets just past each blank line that is followed by more text
+fn paragraph_ends(text: &str) -> Vec<usize> {
+    let mut ends = Vec::new();
+    let mut offset = 0;
+    let mut previous_blank = false;
+    for line in text.split_inclusive('\n') {
+        let blank = line.trim().is_empty();
+        if previous_blank && !blank {
+            ends.push(offset);
+        }
+        previous_blank = blank;
+        offset += line.len();
+    }
+    ends
+}
+
+/// Offsets just past each sentence and the whitespace after it
+fn sentence_ends(text: &str) -> Vec<usize> {
+    let mut ends = Vec::new();
+    let mut after_terminal = false;
+    let mut in_gap = false;
+    for (i, c) in text.char_indices() {
+        if in_gap && !c.is_whitespace() {
+            ends.push(i);
+            in_gap = false;
+        }
+        if c == '\n' || (after_terminal && c.is_whitespace()) {
+            in_gap = true;
+        }
+        after_terminal = match c {
+            '.' | '!' | '?' | '。' | '！' | '？' => true,
+            // Closing quotes and brackets still belong to the sentence
+            '"' | '\'' | ')' | ']' | '”' | '’' => after_terminal,
+            _ => false,
+        };
+    }
+    ends
+}
+
+/// Offsets just past each run of whitespace
+fn word_ends(text: &str) -> Vec<usize> {
+    let mut ends = Vec::new();
+    let mut in_space = false;
+    for (i, c) in text.char_indices() {
+        if in_space && !c.is_whitespace() {
+            ends.push(i);
+        }
+        in_space = c.is_whitespace();
+    }
+    ends
+}
+
+/// Offsets of the characters that would push a piece over `max_weight`
+fn weight_ends(text: &str, max_weight: usize) -> Vec<usize> {
+    let mut ends = Vec::new();
+    let mut current = 0;
+    for (i, c) in text.char_indices() {
+        let size = char_weight(c);
+        if current > 0 && current + size > max_weight {
+            ends.push(i);
+            current = 0;
+        }
+        current += size;
+    }
+    ends
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts<'a>(segments: &[Segment<'a>]) -> Vec<&'a str> {
+        segments.iter().map(|s| s.text).collect()
+    }
+
+    #[test]
+    fn short_text_is_one_segment() {
+        let text = "  One sentence. Another one.\n";
+        let segments = segment(text, 100);
+        assert_eq!(texts(&segments), ["One sentence. Another one."]);
+        assert_eq!(segments[0].offset, 2);
+    }
+
+    #[test]
+    fn splits_on_paragraphs_before_sentences() {
+        let text = "First paragraph here.\n\nSecond paragraph. It has two sentences.";
+        // 40 characters: each paragraph fits, both together don't
+        let segments = segment(text, 10);
+        assert_eq!(
+            texts(&segments),
+            [
+                "First paragraph here.",
+                "Second paragraph. It has two sentences."
+            ]
+        );
+    }
+
+    #[test]
+    fn long_paragraph_is_split_into_sentences() {
+        let text = "This is the first sentence. \"Is this the second?\" Yes, and a third one.";
+        let segments = segment(text, 8);
+        assert_eq!(
+            texts(&segments),
+            [
+                "This is the first sentence.",
+                "\"Is this the second?\"",
+                "Yes, and a third one."
+            ]
+        );
+    }
+
+    #[test]
+    fn segments_point_into_the_text() {
+        let text = "Ünïcödé wörds everywhere. ".repeat(50) + "\n\n" + &"Another block. ".repeat(30);
+        let segments = segment(&text, 20);
+        assert!(segments.len() > 1);
+
+        let mut previous_end = 0;
+        for s in &segments {
+            assert_eq!(&text[s.offset..s.end()], s.text);
+            assert!(estimate_tokens(s.text) <= 20);
+            assert!(text[previous_end..s.offset].trim().is_empty());
+            previous_end = s.end();
+        }
+        assert!(text[previous_end..].trim().is_empty());
+    }
+
+    #[test]
+    fn chinese_counts_about_a_token_per_character() {
+        assert_eq!(estimate_tokens("Hello, world"), 3);
+        assert_eq!(estimate_tokens("你好，世界"), 5);
+
+        // No spaces or sentence ends to split on, so it is cut by weight
+        let text = "语法检查".repeat(10);
+        let segments = segment(&text, 8);
+        assert_eq!(segments.len(), 5);
+        assert!(segments.iter().all(|s| estimate_tokens(s.text) <= 8));
+    }
+
+    #[test]
+    fn words_longer_than_the_budget_are_cut() {
+        let text = "x".repeat(30);
+        let segments = segment(&text, 2);
+        assert_eq!(segments.len(), 4);
+        assert!(segments.iter().all(|s| s.text.len() <= 8));
+    }
+}
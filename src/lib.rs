@@ -1,6 +1,8 @@
 //! Akkurate - A grammar assistant for Linux powered by Gemini AI
 
 pub mod api;
+pub mod cli;
+pub mod clipboard;
 pub mod config;
 pub mod core;
 pub mod ui;
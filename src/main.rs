@@ -1,6 +1,16 @@
-use akkurate::ui::{App, AppFlags};
-use clap::Parser;
-use iced::{self, Font};
+use akkurate::api::{CheckResult, GeminiClient};
+use akkurate::cli::config::ConfigCommand;
+use akkurate::clipboard::Clipboard;
+use akkurate::config::settings::ClipboardConfig;
+use akkurate::config::{
+    load_config, prompts_dir, set_config_path, upgrade_config, AppConfig, ConfigOverrides,
+};
+use akkurate::core::{GrammarChecker, PromptTemplates};
+use akkurate::ui::notification::{self, NotificationAction};
+use akkurate::ui::{overlay, App, AppFlags, Language, WINDOW_TITLE};
+use clap::{Parser, Subcommand};
+use iced::{self, window, Font, Point, Size};
+use std::path::PathBuf;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
 const NOTO_SANS_CJK: &[u8] = include_bytes!("../assets/NotoSansCJK-Regular.ttc");
@@ -8,7 +18,10 @@ const NOTO_SANS_CJK: &[u8] = include_bytes!("../assets/NotoSansCJK-Regular.ttc")
 #[derive(Parser, Debug)]
 #[command(author, version, about = "Akkurate - Grammar Assistant for Linux")]
 struct Args {
-    /// Check grammar for selected text (reads PRIMARY selection via wl-paste)
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Check grammar for selected text (reads the PRIMARY selection)
     #[arg(short = 's', long)]
     check_selection: bool,
 
@@ -19,23 +32,148 @@ struct Args {
     /// Enhance provided text
     #[arg(long, value_name = "TEXT")]
     enhance: Option<String>,
+
+    /// Show the popup as an undecorated, always-on-top window, moved next to the pointer on Hyprland, Sway and X11
+    #[arg(long)]
+    overlay: bool,
+
+    /// With -s, don't open a window: copy the corrected text and show a desktop notification
+    #[arg(long, requires = "check_selection", conflicts_with_all = ["enhance", "overlay"])]
+    notify: bool,
+
+    /// Use this config file instead of ~/.config/akkurate/config.toml [env: AKKURATE_CONFIG]
+    #[arg(long, value_name = "PATH")]
+    config: Option<PathBuf>,
+
+    /// Named profile from the config file for this run [env: AKKURATE_PROFILE]
+    #[arg(long, value_name = "NAME")]
+    profile: Option<String>,
+
+    /// Style preset for this run [env: AKKURATE_PRESET]
+    #[arg(long, value_name = "KEY")]
+    preset: Option<String>,
+
+    /// Gemini model for this run [env: AKKURATE_MODEL]
+    #[arg(long, value_name = "NAME")]
+    model: Option<String>,
+
+    /// Interface language for this run [env: AKKURATE_UI_LANG]
+    #[arg(long, value_name = "LANG", value_parser = ["chinese", "english"])]
+    ui_lang: Option<String>,
+
+    /// Send every request to the API instead of reusing cached results [env: AKKURATE_NO_CACHE]
+    #[arg(long)]
+    no_cache: bool,
+
+    /// Don't send anything over the network; texts are checked with the built-in rules only [env: AKKURATE_OFFLINE]
+    #[arg(long)]
+    offline: bool,
+
+    /// Save API responses to DIR, or answer from them without sending anything [env: AKKURATE_FIXTURES]
+    #[arg(long, value_name = "record:DIR|replay:DIR")]
+    fixtures: Option<String>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Inspect, change and validate the configuration
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommand,
+    },
+    /// List the style presets in picker order
+    Presets {
+        /// Include hidden presets
+        #[arg(long)]
+        all: bool,
+    },
+    /// Show token usage and estimated cost per day
+    Stats {
+        /// Number of most recent days to list
+        #[arg(long, default_value_t = 14)]
+        days: usize,
+    },
 }
 
-/// Read PRIMARY selection using wl-paste
-#[cfg(target_os = "linux")]
-fn get_selection() -> Option<String> {
-    std::process::Command::new("wl-paste")
-        .args(["--primary", "--no-newline"])
-        .output()
-        .ok()
-        .filter(|o| o.status.success())
-        .and_then(|o| String::from_utf8(o.stdout).ok())
+impl Args {
+    /// Config overrides given on the command line
+    fn overrides(&self) -> ConfigOverrides {
+        ConfigOverrides {
+            config_path: self.config.clone(),
+            profile: self.profile.clone(),
+            preset: self.preset.clone(),
+            model: self.model.clone(),
+            ui_language: self.ui_lang.clone(),
+            no_cache: self.no_cache,
+            offline: self.offline,
+            fixtures: self.fixtures.clone(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Read the configured selection (PRIMARY by default), ignoring whitespace-only text
+fn get_selection(config: &ClipboardConfig) -> Option<String> {
+    Clipboard::from_config(config)
+        .read(config.selection_source)
         .filter(|s| !s.trim().is_empty())
 }
 
-#[cfg(not(target_os = "linux"))]
-fn get_selection() -> Option<String> {
-    None
+/// Check text without a window, copy the correction and report via notification
+///
+/// Returns the result when the user asks to see the details.
+fn check_with_notification(text: &str, config: &AppConfig) -> Option<CheckResult> {
+    let language = Language::from_config(&config.preferences.language);
+
+    // Without a key, the built-in rules still catch the common mistakes
+    let checker = match GeminiClient::from_config(config) {
+        Some(client) => {
+            let mut templates = PromptTemplates::new();
+            if let Some(dir) = prompts_dir() {
+                if let Err(e) = templates.load_overrides(&dir) {
+                    tracing::warn!("{:#}", e);
+                }
+            }
+            GrammarChecker::new(client.with_templates(templates))
+                .with_limits(config.api.chunk_tokens, config.api.max_parallel_requests)
+        }
+        None if config.is_offline() => {
+            tracing::info!("Offline mode is on, checking with the built-in rules only");
+            GrammarChecker::offline()
+        }
+        None => {
+            tracing::info!("No API key configured, checking with the built-in rules only");
+            GrammarChecker::offline()
+        }
+    }
+    .with_chinese_explanations(language == Language::Chinese);
+
+    let runtime = tokio::runtime::Runtime::new().expect("Failed to start async runtime");
+    let result = match runtime.block_on(checker.check_grammar(text, language.display_name())) {
+        Ok(result) => result,
+        Err(e) => {
+            tracing::error!("Check failed: {:#}", e);
+            let _ = notification::notify_error(&e.to_string(), language);
+            std::process::exit(1);
+        }
+    };
+
+    let clipboard = Clipboard::from_config(&config.clipboard);
+    let previous = clipboard.save_all(&config.clipboard.copy_targets);
+    clipboard.write_all(&config.clipboard.copy_targets, &result.corrected_text);
+
+    match notification::notify_check_result(&result, language) {
+        Ok(NotificationAction::OpenDetails) => Some(result),
+        Ok(NotificationAction::UndoCopy) => {
+            clipboard.restore(&previous);
+            None
+        }
+        Ok(NotificationAction::Dismissed) => None,
+        Err(e) => {
+            tracing::warn!("Failed to show notification: {:#}", e);
+            None
+        }
+    }
 }
 
 fn main() -> iced::Result {
@@ -47,6 +185,29 @@ fn main() -> iced::Result {
     tracing::info!("Starting Akkurate...");
 
     let args = Args::parse();
+    let overrides = ConfigOverrides::from_env().merge(args.overrides());
+    if let Some(ref path) = overrides.config_path {
+        set_config_path(path.clone());
+    }
+
+    match args.command {
+        Some(Command::Config { command }) => {
+            std::process::exit(akkurate::cli::config::run(command, &overrides))
+        }
+        Some(Command::Presets { all }) => {
+            std::process::exit(akkurate::cli::presets::list(&overrides, all))
+        }
+        Some(Command::Stats { days }) => {
+            std::process::exit(akkurate::cli::stats::show(&overrides, days))
+        }
+        None => {}
+    }
+
+    // Only here, so that the subcommands above never rewrite the file
+    if let Err(e) = upgrade_config() {
+        tracing::warn!("Failed to upgrade config file: {:#}", e);
+    }
+    let config = overrides.applied(&load_config().unwrap_or_default());
 
     // Compute flags before consuming args
     let has_check = args.check.is_some();
@@ -54,7 +215,7 @@ fn main() -> iced::Result {
 
     // Get text from selection if --check-selection is used
     let initial_text = if args.check_selection {
-        match get_selection() {
+        match get_selection(&config.clipboard) {
             Some(text) => {
                 tracing::info!("Got selection: {} chars", text.len());
                 Some(text)
@@ -68,14 +229,49 @@ fn main() -> iced::Result {
         args.check.or(args.enhance)
     };
 
+    let notify_only =
+        args.check_selection && !has_enhance && (args.notify || config.preferences.notify_only);
+    let initial_result = match initial_text {
+        Some(ref text) if notify_only => match check_with_notification(text, &config) {
+            Some(result) => Some(result),
+            None => return Ok(()),
+        },
+        _ => None,
+    };
+
     let is_popup = initial_text.is_some();
     let auto_check = args.check_selection || has_check;
     let auto_enhance = has_enhance;
 
-    let (window_size, resizable, decorations) = if is_popup {
-        ((500.0, 600.0), true, true)
+    let overlay = is_popup && (args.overlay || config.preferences.overlay_popup);
+
+    let window = if overlay {
+        window::Settings {
+            size: Size::from(overlay::SIZE),
+            position: overlay::initial_position()
+                .map(|(x, y)| window::Position::Specific(Point::new(x, y)))
+                .unwrap_or_default(),
+            decorations: false,
+            level: window::Level::AlwaysOnTop,
+            #[cfg(target_os = "linux")]
+            platform_specific: window::settings::PlatformSpecific {
+                application_id: overlay::APP_ID.to_string(),
+                ..Default::default()
+            },
+            ..Default::default()
+        }
     } else {
-        ((900.0, 700.0), true, true)
+        let size = if is_popup {
+            (500.0, 600.0)
+        } else {
+            (900.0, 700.0)
+        };
+        window::Settings {
+            size: Size::from(size),
+            resizable: true,
+            decorations: true,
+            ..Default::default()
+        }
     };
 
     let flags = AppFlags {
@@ -84,15 +280,16 @@ fn main() -> iced::Result {
         auto_enhance,
         check_clipboard: false,
         enhance_clipboard: false,
+        overlay,
+        initial_result,
+        overrides,
     };
 
-    iced::application("Akkurate - 语法助手", App::update, App::view)
+    iced::application(WINDOW_TITLE, App::update, App::view)
         .subscription(App::subscription)
         .theme(App::theme)
         .default_font(Font::with_name("Noto Sans CJK SC"))
         .font(NOTO_SANS_CJK)
-        .window_size(window_size)
-        .resizable(resizable)
-        .decorations(decorations)
+        .window(window)
         .run_with(|| App::new(flags))
 }
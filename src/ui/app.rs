@@ -1,14 +1,38 @@
 //! Main iced application
 
-use crate::api::{CheckResult, EnhanceResult, GeminiClient};
-use crate::config::{load_config, save_config, AppConfig};
-use crate::core::PresetManager;
+use crate::api::usage::{this_month, today};
+use crate::api::{
+    clear_cache, CheckResult, EnhanceResult, GeminiClient, GrammarIssue, TokenUsage, UsageLog,
+};
+use crate::clipboard::{Clipboard, Selection};
+use crate::config::settings::{Provider, THEMES};
+use crate::config::{
+    config_path, load_checked, presets_path, prompts_dir, save_config, AppConfig, CheckedConfig,
+    ConfigIssue, ConfigOverrides,
+};
+use crate::core::{
+    Dialect, ExampleRewrite, GrammarChecker, PresetManager, PromptKind, PromptTemplates,
+    StylePreset,
+};
 use crate::ui::i18n::Language;
+use crate::ui::shortcuts::{Action, Shortcuts};
+use anyhow::Context;
+use iced::futures::SinkExt;
+use iced::widget::text_editor::{Binding, KeyPress};
 use iced::widget::{
-    button, column, container, horizontal_rule, horizontal_space, pick_list, row, scrollable, text,
-    text_editor, text_input, vertical_space,
+    button, checkbox, column, container, horizontal_rule, horizontal_space, pick_list, row,
+    scrollable, text, text_editor, text_input, vertical_space,
 };
-use iced::{Element, Length, Padding, Subscription, Task, Theme};
+use iced::{keyboard, window, Element, Length, Padding, Subscription, Task, Theme};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+
+/// How long to wait for an editor to finish writing a changed config file
+const CONFIG_SETTLE_DELAY: Duration = Duration::from_millis(200);
+
+/// Title of the main window
+pub const WINDOW_TITLE: &str = "Akkurate - 语法助手";
 
 // Shortcut commands for different desktop environments
 const SWAY_CMD: &str = "bindsym $mod+g exec akkurate -s";
@@ -18,9 +42,20 @@ const KDE_CMD: &str = "akkurate -s";
 /// Application state
 pub struct App {
     // Core state
+    overrides: ConfigOverrides,
+    /// Modification times of the config files, for live reload
+    config_stamp: ConfigStamp,
+    /// Effective configuration (file + environment/CLI overrides)
     config: AppConfig,
+    /// Configuration as stored on disk, which is what gets saved
+    file_config: AppConfig,
+    /// Whether config.toml parsed; saving defaults over it would lose the file
+    file_loaded: bool,
     gemini_client: Option<GeminiClient>,
     preset_manager: PresetManager,
+    templates: PromptTemplates,
+    clipboard: Clipboard,
+    shortcuts: Shortcuts,
 
     // UI state
     current_view: View,
@@ -29,17 +64,34 @@ pub struct App {
     result_text: text_editor::Content,
     tail_text: String,
     selected_preset: String,
+    issues: Vec<GrammarIssue>,
+    selected_issue: Option<usize>,
     is_loading: bool,
+    /// Parts of a long text checked so far, out of the total
+    check_progress: Option<(usize, usize)>,
     error_message: Option<String>,
     show_setup_guide: bool,
 
     // Settings state
+    config_issues: Vec<ConfigIssue>,
     api_key_input: String,
+    /// The key field holds unsaved input that reloads must not replace
+    api_key_edited: bool,
     theme_preference: String,
     language: Language,
 
+    // Preset editor state
+    preset_draft: Option<PresetDraft>,
+
     // Clipboard message
     clipboard_msg: Option<String>,
+    /// Token usage as of the last request
+    usage: UsageLog,
+
+    // Popup state
+    is_popup: bool,
+    overlay: bool,
+    overlay_focused: bool,
 }
 
 #[derive(Debug, Clone, Default, PartialEq)]
@@ -47,10 +99,122 @@ pub enum View {
     #[default]
     Main,
     Settings,
+    Presets,
     Help,
     Popup,
 }
 
+/// Preset being edited in the presets view
+struct PresetDraft {
+    /// Key the preset was opened from; `None` for a new preset
+    original: Option<String>,
+    key: String,
+    name: String,
+    tone: String,
+    formality: String,
+    instructions: text_editor::Content,
+    audience: String,
+    target_length: String,
+    dialect: Option<Dialect>,
+    /// Comma-separated
+    banned_words: String,
+    description: String,
+    icon: String,
+    order: String,
+    hidden: bool,
+    /// Not editable here, kept from presets.toml
+    examples: Vec<ExampleRewrite>,
+    /// Localized names, which only apply as long as `name` is unchanged
+    names: BTreeMap<String, String>,
+    original_name: String,
+}
+
+impl PresetDraft {
+    fn new(original: Option<String>, key: String, preset: &StylePreset) -> Self {
+        Self {
+            original,
+            key,
+            name: preset.name.clone(),
+            tone: preset.tone.clone(),
+            formality: preset.formality.clone(),
+            instructions: text_editor::Content::with_text(&preset.instructions),
+            audience: preset.audience.clone().unwrap_or_default(),
+            target_length: preset.target_length.clone().unwrap_or_default(),
+            dialect: preset.dialect,
+            banned_words: preset.banned_words.join(", "),
+            description: preset.description.clone().unwrap_or_default(),
+            icon: preset.icon.clone().unwrap_or_default(),
+            order: preset.order.map(|o| o.to_string()).unwrap_or_default(),
+            hidden: preset.hidden,
+            examples: preset.examples.clone(),
+            names: preset.names.clone(),
+            original_name: preset.name.clone(),
+        }
+    }
+
+    fn preset(&self) -> StylePreset {
+        let optional = |value: &str| Some(value.trim().to_string()).filter(|v| !v.is_empty());
+        let name = self.name.trim().to_string();
+        // The localized names would otherwise keep hiding a new name
+        let names = if name == self.original_name {
+            self.names.clone()
+        } else {
+            BTreeMap::new()
+        };
+        StylePreset {
+            name,
+            tone: self.tone.trim().to_string(),
+            formality: self.formality.trim().to_string(),
+            instructions: self.instructions.text().trim().to_string(),
+            audience: optional(&self.audience),
+            target_length: optional(&self.target_length),
+            dialect: self.dialect,
+            banned_words: self
+                .banned_words
+                .split(',')
+                .map(str::trim)
+                .filter(|word| !word.is_empty())
+                .map(String::from)
+                .collect(),
+            description: optional(&self.description),
+            icon: optional(&self.icon),
+            // Checked by `App::save_draft`
+            order: self.order.trim().parse().ok(),
+            hidden: self.hidden,
+            examples: self.examples.clone(),
+            names,
+        }
+    }
+}
+
+/// Single-line field of the preset editor
+#[derive(Debug, Clone, Copy)]
+pub enum PresetField {
+    Key,
+    Name,
+    Tone,
+    Formality,
+    Audience,
+    TargetLength,
+    BannedWords,
+    Description,
+    Icon,
+    Order,
+}
+
+/// Entry of the dialect picker; `None` leaves the dialect unspecified
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DialectOption(Option<Dialect>);
+
+impl std::fmt::Display for DialectOption {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.0 {
+            Some(dialect) => dialect.fmt(f),
+            None => f.write_str("-"),
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum Message {
     // Navigation
@@ -74,6 +238,7 @@ pub enum Message {
     PasteAndEnhance,
 
     // Async results
+    CheckProgress(usize, usize),
     CheckComplete(Result<CheckResult, String>),
     EnhanceComplete(Result<EnhanceResult, String>),
 
@@ -82,10 +247,61 @@ pub enum Message {
     SaveApiKey,
     ThemeChanged(String),
     LanguageChanged(Language),
+    ProfileSelected(ProfileOption),
+    CacheToggled(bool),
+    ClearCache,
     ToggleSetupGuide,
 
+    // Preset editor
+    EditPreset(String),
+    NewPreset,
+    DuplicatePreset,
+    DeletePreset,
+    PresetFieldChanged(PresetField, String),
+    PresetDialectChanged(DialectOption),
+    PresetHiddenToggled(bool),
+    PresetInstructionsAction(text_editor::Action),
+    SavePreset,
+
     // Clear clipboard message
     ClearClipboardMsg,
+
+    // Config live reload
+    ConfigFilesChanged,
+
+    // Keyboard shortcuts
+    KeyPressed(keyboard::Key, keyboard::Modifiers, iced::event::Status),
+
+    // Overlay popup
+    OverlayOpened,
+    OverlayFocusChanged(bool),
+    CloseOverlay,
+}
+
+/// Entry of the preset pickers
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PresetOption {
+    key: String,
+    label: String,
+}
+
+impl std::fmt::Display for PresetOption {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.label)
+    }
+}
+
+/// Entry of the profile picker; an empty name means no profile
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProfileOption {
+    name: String,
+    label: String,
+}
+
+impl std::fmt::Display for ProfileOption {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.label)
+    }
 }
 
 /// Flags passed to the application on startup
@@ -96,62 +312,100 @@ pub struct AppFlags {
     pub auto_enhance: bool,
     pub check_clipboard: bool,
     pub enhance_clipboard: bool,
+    pub overlay: bool,
+    /// Result of a check that already ran (e.g. from a notification)
+    pub initial_result: Option<CheckResult>,
+    /// Environment and command-line overrides of the config file
+    pub overrides: ConfigOverrides,
 }
 
 impl App {
     pub fn new(flags: AppFlags) -> (Self, Task<Message>) {
-        let config = load_config().unwrap_or_default();
-        let show_setup_guide = config.api.gemini_key.is_empty();
-
-        let gemini_client = if !config.api.gemini_key.is_empty() {
-            Some(GeminiClient::new(config.api.gemini_key.clone()))
+        let CheckedConfig {
+            file_config,
+            file_loaded,
+            config,
+            presets: preset_manager,
+            templates,
+            issues: config_issues,
+            ..
+        } = load_checked(&flags.overrides);
+        let show_setup_guide =
+            config.api.provider == Provider::Gemini && config.api.gemini_key.is_empty();
+
+        let gemini_client = GeminiClient::from_config(&config)
+            .map(|client| client.with_templates(templates.clone()));
+
+        let language = Language::from_config(&config.preferences.language);
+
+        // An unknown default preset is reported in the settings view
+        let selected_preset = if preset_manager
+            .get(&config.preferences.default_preset)
+            .is_some()
+        {
+            config.preferences.default_preset.clone()
         } else {
-            None
+            "casual".to_string()
         };
 
-        let mut preset_manager = PresetManager::new();
-        if let Some(config_dir) = crate::config::config_path() {
-            if let Some(parent) = config_dir.parent() {
-                let presets_path = parent.join("presets.toml");
-                let _ = preset_manager.load_custom_presets(&presets_path);
-            }
-        }
-
-        let language = match config.preferences.language.as_str() {
-            "english" => Language::English,
-            _ => Language::Chinese,
-        };
-
-        let initial_view = if flags.check_clipboard || flags.enhance_clipboard {
+        let initial_view = if flags.overlay || flags.check_clipboard || flags.enhance_clipboard {
             View::Popup
         } else {
             View::Main
         };
 
+        let clipboard = Clipboard::from_config(&config.clipboard);
+        let shortcuts = Shortcuts::from_config(&config.keybindings);
+        let is_popup = flags.initial_text.is_some()
+            || flags.overlay
+            || flags.check_clipboard
+            || flags.enhance_clipboard;
+
         let mut app = Self {
-            selected_preset: config.preferences.default_preset.clone(),
+            selected_preset,
             theme_preference: config.preferences.theme.clone(),
-            api_key_input: config.api.gemini_key.clone(),
+            api_key_input: file_api_key(&file_config, &config.profile),
+            api_key_edited: false,
+            config_issues,
             language,
+            overrides: flags.overrides.clone(),
+            config_stamp: config_stamp(),
             config,
+            file_config,
+            file_loaded,
             gemini_client,
             preset_manager,
+            templates,
+            clipboard,
+            shortcuts,
             current_view: initial_view,
             input_content: text_editor::Content::new(),
             explanation_text: String::new(),
             result_text: text_editor::Content::new(),
             tail_text: String::new(),
+            issues: Vec::new(),
+            selected_issue: None,
             is_loading: false,
+            check_progress: None,
             error_message: None,
             show_setup_guide,
+            preset_draft: None,
             clipboard_msg: None,
+            usage: UsageLog::load(),
+            is_popup,
+            overlay: flags.overlay,
+            overlay_focused: false,
         };
 
         // Handle auto-actions from flags
         let task = if let Some(ref text) = flags.initial_text {
             // Text provided directly via CLI
             app.input_content = text_editor::Content::with_text(text);
-            if flags.auto_check {
+            if let Some(result) = flags.initial_result {
+                Task::perform(async move { result }, |result| {
+                    Message::CheckComplete(Ok(result))
+                })
+            } else if flags.auto_check {
                 Task::perform(async {}, |_| Message::CheckGrammar)
             } else if flags.auto_enhance {
                 Task::perform(async {}, |_| Message::EnhanceText)
@@ -174,16 +428,317 @@ impl App {
     }
 
     pub fn subscription(&self) -> Subscription<Message> {
-        Subscription::none()
+        let keys = iced::event::listen_with(key_event);
+        let config_watch = Subscription::run(watch_config_files);
+
+        if self.overlay {
+            Subscription::batch([keys, config_watch, iced::event::listen_with(overlay_event)])
+        } else {
+            Subscription::batch([keys, config_watch])
+        }
     }
 
     fn s(&self) -> &'static crate::ui::i18n::Strings {
         self.language.strings()
     }
 
+    /// Key bindings for text editors, leaving reserved combinations to the shortcuts
+    fn editor_binding(&self, key_press: KeyPress) -> Option<Binding<Message>> {
+        if self
+            .shortcuts
+            .reserves(&key_press.key, key_press.modifiers, self.is_popup)
+        {
+            None
+        } else {
+            Binding::from_key_press(key_press)
+        }
+    }
+
+    /// Get the keys of the presets offered in pickers, in display order
+    fn preset_keys(&self) -> Vec<String> {
+        self.preset_manager
+            .visible_keys()
+            .into_iter()
+            .cloned()
+            .collect()
+    }
+
+    /// Localized name of a preset with its icon
+    fn preset_label(&self, key: &str) -> String {
+        match self.preset_manager.get(key) {
+            Some(preset) => preset.label(self.language.config_name()),
+            None => key.to_string(),
+        }
+    }
+
+    /// Entries of the preset pickers
+    fn preset_options(&self) -> Vec<PresetOption> {
+        self.preset_keys()
+            .into_iter()
+            .map(|key| self.preset_option(key))
+            .collect()
+    }
+
+    fn preset_option(&self, key: String) -> PresetOption {
+        PresetOption {
+            label: self.preset_label(&key),
+            key,
+        }
+    }
+
+    /// Reload token usage after a request
+    fn refresh_usage(&mut self) {
+        self.usage = UsageLog::load();
+    }
+
+    /// Warning shown for as long as the monthly budget is used up
+    fn budget_note(&self) -> Element<'_, Message> {
+        if self.usage.over_budget(&self.config.usage).is_some() {
+            text(self.s().budget_exceeded).size(12).into()
+        } else {
+            column![].into()
+        }
+    }
+
+    /// One line of usage figures for the settings view
+    fn usage_line(&self, label: &str, usage: &TokenUsage) -> String {
+        let prices = &self.config.usage;
+        let mut line = self
+            .s()
+            .usage_summary
+            .replace("{label}", label)
+            .replace("{requests}", &usage.requests.to_string())
+            .replace("{prompt}", &usage.prompt_tokens.to_string())
+            .replace("{output}", &usage.output_tokens.to_string());
+        if prices.input_price > 0.0 || prices.output_price > 0.0 {
+            line.push_str(&format!(" · ${:.2}", usage.cost(prices)));
+        }
+        line
+    }
+
+    /// Label of the check button while a check runs
+    fn progress_label(&self) -> String {
+        let s = self.s();
+        match self.check_progress {
+            Some((done, total)) if total > 1 => s
+                .checked_parts
+                .replace("{done}", &done.to_string())
+                .replace("{total}", &total.to_string()),
+            _ => s.processing.to_string(),
+        }
+    }
+
+    /// Format the issue list, marking the issue selected with Tab/Shift+Tab
+    fn format_issues(&self) -> String {
+        let s = self.s();
+        let mut output = String::new();
+        if self.issues.is_empty() {
+            output.push_str(s.no_issues);
+            output.push_str("\n\n");
+        } else {
+            output.push_str(&s.found_issues.replace("{}", &self.issues.len().to_string()));
+            output.push_str("\n\n");
+            for (i, issue) in self.issues.iter().enumerate() {
+                let marker = if self.selected_issue == Some(i) {
+                    ">"
+                } else {
+                    " "
+                };
+                output.push_str(&format!(
+                    "{} {}. \"{}\" -> \"{}\"\n     {} ({})\n\n",
+                    marker,
+                    i + 1,
+                    issue.original,
+                    issue.corrected,
+                    issue.explanation,
+                    issue.rule
+                ));
+            }
+        }
+        output.push_str("---\n");
+        output.push_str(s.corrected_text);
+        output
+    }
+
+    /// Run the action bound to a shortcut
+    fn run_shortcut(&mut self, action: Action) -> Task<Message> {
+        match action {
+            Action::Check | Action::Enhance if self.is_loading => Task::none(),
+            Action::Check => self.update(Message::CheckGrammar),
+            Action::Enhance => self.update(Message::EnhanceText),
+            Action::CopyResult => self.update(Message::CopyResult),
+            Action::Close if self.is_popup => iced::exit(),
+            Action::Close => Task::none(),
+            Action::NextIssue | Action::PreviousIssue => {
+                let count = self.issues.len();
+                if count > 0 {
+                    self.selected_issue = Some(match (action, self.selected_issue) {
+                        (Action::NextIssue, Some(i)) => (i + 1) % count,
+                        (Action::NextIssue, None) => 0,
+                        (_, Some(i)) => (i + count - 1) % count,
+                        (_, None) => count - 1,
+                    });
+                    self.explanation_text = self.format_issues();
+                }
+                Task::none()
+            }
+            Action::Preset(index) => {
+                if let Some(key) = self.preset_keys().into_iter().nth(index) {
+                    self.selected_preset = key;
+                }
+                Task::none()
+            }
+        }
+    }
+
+    /// Change a setting in the on-disk config, save it and apply the result
+    ///
+    /// Only the file config is written, so overrides never end up on disk.
+    fn change_config(&mut self, change: impl FnOnce(&mut AppConfig)) -> anyhow::Result<()> {
+        change(&mut self.file_config);
+        let saved = if self.file_loaded {
+            save_config(&self.file_config)
+        } else {
+            Err(anyhow::anyhow!(self.s().config_not_saved))
+        };
+        // Our own write shouldn't trigger a reload
+        self.config_stamp = config_stamp();
+        self.config = self.overrides.applied(&self.file_config);
+        self.apply_config();
+        saved
+    }
+
+    /// Update the state derived from the effective config
+    fn apply_config(&mut self) {
+        self.theme_preference = self.config.preferences.theme.clone();
+        self.language = Language::from_config(&self.config.preferences.language);
+        if !self.api_key_edited {
+            self.api_key_input = file_api_key(&self.file_config, &self.config.profile);
+        }
+        self.clipboard = Clipboard::from_config(&self.config.clipboard);
+        self.shortcuts = Shortcuts::from_config(&self.config.keybindings);
+        self.gemini_client = GeminiClient::from_config(&self.config)
+            .map(|client| client.with_templates(self.templates.clone()));
+    }
+
+    /// Names of the profiles to choose from, starting with "no profile"
+    fn profile_options(&self) -> Vec<ProfileOption> {
+        let none = ProfileOption {
+            name: String::new(),
+            label: self.s().no_profile.to_string(),
+        };
+        std::iter::once(none)
+            .chain(self.file_config.profiles.keys().map(|name| ProfileOption {
+                name: name.clone(),
+                label: name.clone(),
+            }))
+            .collect()
+    }
+
+    /// Reload config.toml and presets.toml after they changed on disk
+    ///
+    /// The input text and results are kept. If config.toml no longer parses
+    /// the current settings stay in effect and the problem is reported.
+    fn reload_config(&mut self) {
+        let checked = load_checked(&self.overrides);
+        self.config_issues = checked.issues;
+        self.preset_manager = checked.presets;
+        self.templates = checked.templates;
+
+        self.file_loaded = checked.file_loaded;
+        if checked.file_loaded {
+            self.file_config = checked.file_config;
+            self.config = checked.config;
+            self.apply_config();
+        }
+
+        if self.preset_manager.get(&self.selected_preset).is_none() {
+            self.selected_preset = if self
+                .preset_manager
+                .get(&self.config.preferences.default_preset)
+                .is_some()
+            {
+                self.config.preferences.default_preset.clone()
+            } else {
+                "casual".to_string()
+            };
+        }
+        tracing::info!("Reloaded configuration");
+    }
+
+    /// Load a preset into the editor
+    fn open_preset(&mut self, key: String) {
+        self.preset_draft = self
+            .preset_manager
+            .get(&key)
+            .map(|preset| PresetDraft::new(Some(key.clone()), key, preset));
+    }
+
+    /// Validate the edited preset, add it and write presets.toml
+    fn save_draft(&mut self) -> Result<(), String> {
+        let s = self.s();
+        let Some(ref mut draft) = self.preset_draft else {
+            return Ok(());
+        };
+
+        let key = draft.key.trim().to_string();
+        if key.is_empty()
+            || !key
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        {
+            return Err(s.preset_invalid_key.to_string());
+        }
+        let preset = draft.preset();
+        if preset.name.is_empty() {
+            return Err(s.preset_missing_name.to_string());
+        }
+        if preset.order.is_none() && !draft.order.trim().is_empty() {
+            return Err(s.preset_invalid_order.to_string());
+        }
+        let renamed = draft.original.as_deref() != Some(key.as_str());
+        if renamed && self.preset_manager.get(&key).is_some() {
+            return Err(s.preset_exists.to_string());
+        }
+
+        if let Some(original) = draft.original.replace(key.clone()) {
+            if renamed {
+                self.preset_manager.remove(&original);
+                if self.selected_preset == original {
+                    self.selected_preset = key.clone();
+                }
+            }
+        }
+        draft.key = key.clone();
+        self.preset_manager.add(key, preset);
+
+        self.save_presets()
+            .map_err(|e| format!("{}: {:#}", s.save_failed, e))
+    }
+
+    /// Write the custom presets to presets.toml
+    fn save_presets(&mut self) -> anyhow::Result<()> {
+        let path = presets_path().context("Could not determine presets path")?;
+        let saved = self.preset_manager.save_custom_presets(&path);
+        // Our own write shouldn't trigger a reload
+        self.config_stamp = config_stamp();
+        saved
+    }
+
+    /// Read text from the selection configured for pasting
+    fn clipboard_text(&self) -> Option<String> {
+        self.clipboard.read(self.config.clipboard.paste_source)
+    }
+
     pub fn update(&mut self, message: Message) -> Task<Message> {
         match message {
             Message::NavigateTo(view) => {
+                if view == View::Presets && self.preset_draft.is_none() {
+                    self.open_preset(self.selected_preset.clone());
+                }
+                if view == View::Settings {
+                    self.usage = UsageLog::load();
+                }
                 self.current_view = view;
                 self.clipboard_msg = None;
                 Task::none()
@@ -208,26 +763,46 @@ impl App {
             }
 
             Message::CheckGrammar => {
-                if let Some(client) = &self.gemini_client {
-                    let text = self.input_content.text();
-                    if text.trim().is_empty() {
-                        self.error_message = Some(self.s().enter_text_check.to_string());
-                        return Task::none();
-                    }
-
-                    self.is_loading = true;
-                    self.error_message = None;
-                    let client = client.clone();
-                    let lang = self.language.display_name();
+                let text = self.input_content.text();
+                if text.trim().is_empty() {
+                    self.error_message = Some(self.s().enter_text_check.to_string());
+                    return Task::none();
+                }
 
-                    Task::perform(
-                        async move { client.check_grammar(&text, lang).await },
-                        |result| Message::CheckComplete(result.map_err(|e| e.to_string())),
-                    )
-                } else {
-                    self.error_message = Some(self.s().api_not_configured.to_string());
-                    Task::none()
+                self.is_loading = true;
+                self.check_progress = None;
+                self.error_message = None;
+                let checker = match &self.gemini_client {
+                    Some(client) => GrammarChecker::new(client.clone()).with_limits(
+                        self.config.api.chunk_tokens,
+                        self.config.api.max_parallel_requests,
+                    ),
+                    None => {
+                        let note = if self.config.is_offline() {
+                            self.s().rules_only_offline
+                        } else {
+                            self.s().rules_only
+                        };
+                        self.clipboard_msg = Some(note.to_string());
+                        GrammarChecker::offline()
+                    }
                 }
+                .with_chinese_explanations(self.language == Language::Chinese);
+                let lang = self.language.display_name();
+
+                let check = iced::stream::channel(16, move |mut output| async move {
+                    let mut progress = output.clone();
+                    let result = checker
+                        .check_with_progress(&text, lang, |done, total| {
+                            // Progress is only informative; drop it if the UI lags behind
+                            let _ = progress.try_send(Message::CheckProgress(done, total));
+                        })
+                        .await;
+                    let _ = output
+                        .send(Message::CheckComplete(result.map_err(|e| e.to_string())))
+                        .await;
+                });
+                Task::run(check, |message| message)
             }
 
             Message::EnhanceText => {
@@ -253,41 +828,37 @@ impl App {
                         Task::none()
                     }
                 } else {
-                    self.error_message = Some(self.s().api_not_configured.to_string());
+                    let message = if self.config.is_offline() {
+                        self.s().offline_profile
+                    } else {
+                        self.s().api_not_configured
+                    };
+                    self.error_message = Some(message.to_string());
                     Task::none()
                 }
             }
 
+            Message::CheckProgress(done, total) => {
+                self.check_progress = Some((done, total));
+                Task::none()
+            }
+
             Message::CheckComplete(result) => {
                 self.is_loading = false;
+                self.check_progress = None;
+                self.refresh_usage();
                 match result {
                     Ok(check_result) => {
-                        let s = self.s();
-                        let mut output = String::new();
-                        if check_result.issues.is_empty() {
-                            output.push_str(s.no_issues);
-                            output.push_str("\n\n");
-                        } else {
-                            output.push_str(
-                                &s.found_issues
-                                    .replace("{}", &check_result.issues.len().to_string()),
-                            );
-                            output.push_str("\n\n");
-                            for (i, issue) in check_result.issues.iter().enumerate() {
-                                output.push_str(&format!(
-                                    "{}. \"{}\" -> \"{}\"\n   {} ({})\n\n",
-                                    i + 1,
-                                    issue.original,
-                                    issue.corrected,
-                                    issue.explanation,
-                                    issue.rule
-                                ));
-                            }
+                        if !check_result.unchecked.is_empty() {
+                            self.error_message = Some(format!(
+                                "{}\n{}",
+                                self.s().unchecked_parts,
+                                check_result.unchecked.join("\n")
+                            ));
                         }
-                        output.push_str("---\n");
-                        output.push_str(s.corrected_text);
-
-                        self.explanation_text = output;
+                        self.issues = check_result.issues;
+                        self.selected_issue = None;
+                        self.explanation_text = self.format_issues();
                         self.result_text =
                             text_editor::Content::with_text(&check_result.corrected_text);
                     }
@@ -300,6 +871,7 @@ impl App {
 
             Message::EnhanceComplete(result) => {
                 self.is_loading = false;
+                self.refresh_usage();
                 match result {
                     Ok(enhance_result) => {
                         let s = self.s();
@@ -328,45 +900,9 @@ impl App {
             Message::CopyResult => {
                 let text = self.result_text.text();
                 if !text.is_empty() {
-                    let mut success = false;
-
-                    // On Linux, try wl-copy FIRST because arboard returns false positives on Wayland
-                    #[cfg(target_os = "linux")]
-                    {
-                        tracing::info!("Running on Linux, attempting wl-copy first");
-                        use std::io::Write;
-                        use std::process::{Command, Stdio};
-
-                        if let Ok(mut child) = Command::new("wl-copy").stdin(Stdio::piped()).spawn()
-                        {
-                            if let Some(mut stdin) = child.stdin.take() {
-                                if stdin.write_all(text.as_bytes()).is_ok() {
-                                    tracing::info!("wl-copy write successful");
-                                    success = true;
-                                } else {
-                                    tracing::warn!("wl-copy write failed");
-                                }
-                            }
-                            let _ = child.wait();
-                        } else {
-                            tracing::warn!("Failed to spawn wl-copy");
-                        }
-                    }
-
-                    // Try arboard if wl-copy failed (or if not on Linux)
-                    if !success {
-                        tracing::info!("Attempting arboard copy (fallback/primary)");
-                        if let Ok(mut clipboard) = arboard::Clipboard::new() {
-                            if clipboard.set_text(&text).is_ok() {
-                                tracing::info!("arboard copy successful");
-                                success = true;
-                            } else {
-                                tracing::warn!("arboard copy failed");
-                            }
-                        } else {
-                            tracing::warn!("Failed to initialize arboard clipboard");
-                        }
-                    }
+                    let success = self
+                        .clipboard
+                        .write_all(&self.config.clipboard.copy_targets, &text);
 
                     if success {
                         self.clipboard_msg = Some(if self.language == Language::Chinese {
@@ -386,8 +922,7 @@ impl App {
             }
 
             Message::CopyCommand(cmd) => {
-                if let Ok(mut clipboard) = arboard::Clipboard::new() {
-                    let _ = clipboard.set_text(&cmd);
+                if self.clipboard.write(Selection::Clipboard, &cmd) {
                     self.clipboard_msg = Some(if self.language == Language::Chinese {
                         "已复制!".to_string()
                     } else {
@@ -398,7 +933,7 @@ impl App {
             }
 
             Message::PasteFromClipboard => {
-                let text = get_clipboard_text();
+                let text = self.clipboard_text();
                 if let Some(text) = text {
                     self.input_content = text_editor::Content::with_text(&text);
                 }
@@ -410,21 +945,36 @@ impl App {
                 self.explanation_text.clear();
                 self.result_text = text_editor::Content::new();
                 self.tail_text.clear();
+                self.issues.clear();
+                self.selected_issue = None;
                 self.error_message = None;
                 Task::none()
             }
 
             Message::ApiKeyInputChanged(key) => {
                 self.api_key_input = key;
+                self.api_key_edited = true;
                 Task::none()
             }
 
             Message::SaveApiKey => {
-                self.config.api.gemini_key = self.api_key_input.clone();
-                if let Err(e) = save_config(&self.config) {
+                let key = self.api_key_input.clone();
+                self.api_key_edited = false;
+                // A profile with its own `[api]` section keeps its own key
+                let profile = self.config.profile.clone();
+                let result = self.change_config(|config| {
+                    match config
+                        .profiles
+                        .get_mut(&profile)
+                        .and_then(|p| p.api.as_mut())
+                    {
+                        Some(api) => api.gemini_key = key,
+                        None => config.api.gemini_key = key,
+                    }
+                });
+                if let Err(e) = result {
                     self.error_message = Some(format!("{}: {}", self.s().save_failed, e));
                 } else {
-                    self.gemini_client = Some(GeminiClient::new(self.api_key_input.clone()));
                     self.show_setup_guide = false;
                     self.error_message = None;
                 }
@@ -433,18 +983,172 @@ impl App {
 
             Message::ThemeChanged(theme) => {
                 self.theme_preference = theme.clone();
-                self.config.preferences.theme = theme;
-                let _ = save_config(&self.config);
+                if let Err(e) =
+                    self.change_config(|config| config.preferences.theme = theme.clone())
+                {
+                    self.error_message = Some(format!("{}: {}", self.s().save_failed, e));
+                }
                 Task::none()
             }
 
             Message::LanguageChanged(lang) => {
                 self.language = lang;
-                self.config.preferences.language = match lang {
-                    Language::Chinese => "chinese".to_string(),
-                    Language::English => "english".to_string(),
+                let value = lang.config_name();
+                let profile = self.config.profile.clone();
+                let result = self.change_config(|config| {
+                    match config
+                        .profiles
+                        .get_mut(&profile)
+                        .filter(|p| p.language.is_some())
+                    {
+                        Some(profile) => profile.language = Some(value.to_string()),
+                        None => config.preferences.language = value.to_string(),
+                    }
+                });
+                // After switching, so the message is in the new language
+                if let Err(e) = result {
+                    self.error_message = Some(format!("{}: {}", self.s().save_failed, e));
+                }
+                Task::none()
+            }
+
+            Message::CacheToggled(enabled) => {
+                if let Err(e) = self.change_config(|config| config.cache.enabled = enabled) {
+                    self.error_message = Some(format!("{}: {}", self.s().save_failed, e));
+                }
+                Task::none()
+            }
+
+            Message::ClearCache => {
+                match clear_cache() {
+                    Ok(()) => self.clipboard_msg = Some(self.s().cache_cleared.to_string()),
+                    Err(e) => {
+                        self.error_message = Some(format!("{}: {}", self.s().error_prefix, e))
+                    }
+                }
+                Task::none()
+            }
+
+            Message::ProfileSelected(option) => {
+                // Choosing a profile here wins over `--profile` for this session
+                self.overrides.profile = None;
+                if let Err(e) = self.change_config(|config| config.profile = option.name) {
+                    self.error_message = Some(format!("{}: {}", self.s().save_failed, e));
+                }
+                if self
+                    .preset_manager
+                    .get(&self.config.preferences.default_preset)
+                    .is_some()
+                {
+                    self.selected_preset = self.config.preferences.default_preset.clone();
+                }
+                // Problems loading the files themselves still apply
+                self.config_issues.retain(|issue| {
+                    issue.location.ends_with(".toml") || issue.location == "prompts"
+                });
+                self.config_issues
+                    .extend(crate::config::validate(&self.config, &self.preset_manager));
+                self.show_setup_guide = self.config.api.provider == Provider::Gemini
+                    && self.config.api.gemini_key.is_empty();
+                Task::none()
+            }
+
+            Message::EditPreset(key) => {
+                self.open_preset(key);
+                Task::none()
+            }
+
+            Message::NewPreset => {
+                let preset = StylePreset::default();
+                self.preset_draft = Some(PresetDraft::new(None, String::new(), &preset));
+                self.error_message = None;
+                Task::none()
+            }
+
+            Message::DuplicatePreset => {
+                if let Some(ref draft) = self.preset_draft {
+                    let mut key = format!("{}_copy", draft.key);
+                    let mut n = 2;
+                    while self.preset_manager.get(&key).is_some() {
+                        key = format!("{}_copy{}", draft.key, n);
+                        n += 1;
+                    }
+                    self.preset_draft = Some(PresetDraft::new(None, key, &draft.preset()));
+                    self.error_message = None;
+                }
+                Task::none()
+            }
+
+            Message::DeletePreset => {
+                let Some(key) = self.preset_draft.as_ref().and_then(|d| d.original.clone()) else {
+                    return Task::none();
                 };
-                let _ = save_config(&self.config);
+                if self.preset_manager.remove(&key).is_some() {
+                    match self.save_presets() {
+                        Ok(()) => self.clipboard_msg = Some(self.s().preset_deleted.to_string()),
+                        Err(e) => {
+                            self.error_message = Some(format!("{}: {:#}", self.s().save_failed, e))
+                        }
+                    }
+                    if self.preset_manager.get(&self.selected_preset).is_none() {
+                        self.selected_preset = "casual".to_string();
+                    }
+                    // A deleted override shows the built-in preset again
+                    if self.preset_manager.get(&key).is_some() {
+                        self.open_preset(key);
+                    } else {
+                        self.preset_draft = None;
+                    }
+                }
+                Task::none()
+            }
+
+            Message::PresetFieldChanged(field, value) => {
+                if let Some(ref mut draft) = self.preset_draft {
+                    match field {
+                        PresetField::Key => draft.key = value,
+                        PresetField::Name => draft.name = value,
+                        PresetField::Tone => draft.tone = value,
+                        PresetField::Formality => draft.formality = value,
+                        PresetField::Audience => draft.audience = value,
+                        PresetField::TargetLength => draft.target_length = value,
+                        PresetField::BannedWords => draft.banned_words = value,
+                        PresetField::Description => draft.description = value,
+                        PresetField::Icon => draft.icon = value,
+                        PresetField::Order => draft.order = value,
+                    }
+                }
+                Task::none()
+            }
+
+            Message::PresetDialectChanged(DialectOption(dialect)) => {
+                if let Some(ref mut draft) = self.preset_draft {
+                    draft.dialect = dialect;
+                }
+                Task::none()
+            }
+
+            Message::PresetHiddenToggled(hidden) => {
+                if let Some(ref mut draft) = self.preset_draft {
+                    draft.hidden = hidden;
+                }
+                Task::none()
+            }
+
+            Message::PresetInstructionsAction(action) => {
+                if let Some(ref mut draft) = self.preset_draft {
+                    draft.instructions.perform(action);
+                }
+                Task::none()
+            }
+
+            Message::SavePreset => {
+                if let Err(e) = self.save_draft() {
+                    self.error_message = Some(e);
+                } else {
+                    self.error_message = None;
+                    self.clipboard_msg = Some(self.s().preset_saved.to_string());
+                }
                 Task::none()
             }
 
@@ -461,7 +1165,7 @@ impl App {
             Message::PasteAndCheck => {
                 tracing::info!("PasteAndCheck triggered");
 
-                match get_clipboard_text() {
+                match self.clipboard_text() {
                     Some(text) if !text.is_empty() => {
                         tracing::info!("Clipboard text length: {} chars", text.len());
                         self.input_content = text_editor::Content::with_text(&text);
@@ -478,12 +1182,48 @@ impl App {
             }
 
             Message::PasteAndEnhance => {
-                if let Some(text) = get_clipboard_text() {
+                if let Some(text) = self.clipboard_text() {
                     self.input_content = text_editor::Content::with_text(&text);
                     return Task::perform(async {}, |_| Message::EnhanceText);
                 }
                 Task::none()
             }
+
+            Message::ConfigFilesChanged => {
+                // Our own writes already updated the stamp
+                let stamp = config_stamp();
+                if stamp != self.config_stamp {
+                    self.config_stamp = stamp;
+                    self.reload_config();
+                }
+                Task::none()
+            }
+
+            Message::KeyPressed(key, modifiers, status) => {
+                match self.shortcuts.action(&key, modifiers, status) {
+                    Some(action) => self.run_shortcut(action),
+                    None => Task::none(),
+                }
+            }
+
+            Message::OverlayOpened => {
+                Task::future(async { crate::ui::overlay::place_near_cursor() }).discard()
+            }
+
+            Message::OverlayFocusChanged(focused) => {
+                // Only close once the overlay has actually held focus, so a
+                // compositor that maps it unfocused doesn't dismiss it at once
+                if focused {
+                    self.overlay_focused = true;
+                    Task::none()
+                } else if self.overlay_focused {
+                    iced::exit()
+                } else {
+                    Task::none()
+                }
+            }
+
+            Message::CloseOverlay => iced::exit(),
         }
     }
 
@@ -491,11 +1231,24 @@ impl App {
         let content: Element<Message> = match self.current_view {
             View::Main => self.view_main(),
             View::Settings => self.view_settings(),
+            View::Presets => self.view_presets(),
             View::Help => self.view_help(),
             View::Popup => self.view_popup(),
         };
 
-        if self.current_view == View::Popup {
+        if self.overlay {
+            let close_bar = row![
+                horizontal_space(),
+                button(text("x").size(12))
+                    .style(button::text)
+                    .on_press(Message::CloseOverlay),
+            ];
+            container(column![close_bar, content])
+                .width(Length::Fill)
+                .height(Length::Fill)
+                .padding(Padding::from([4, 10]))
+                .into()
+        } else if self.current_view == View::Popup {
             container(content)
                 .width(Length::Fill)
                 .height(Length::Fill)
@@ -528,6 +1281,7 @@ impl App {
         let nav = row![
             self.nav_button(s.nav_main, View::Main),
             self.nav_button(s.nav_settings, View::Settings),
+            self.nav_button(s.nav_presets, View::Presets),
             self.nav_button(s.nav_help, View::Help),
             horizontal_space(),
             if let Some(ref msg) = self.clipboard_msg {
@@ -539,7 +1293,7 @@ impl App {
                 text(format!(
                     "{}: {}",
                     s.current_style,
-                    s.preset_display_name(&self.selected_preset)
+                    self.preset_label(&self.selected_preset)
                 ))
                 .size(13)
             )
@@ -579,14 +1333,19 @@ impl App {
         };
 
         // Preset selector
-        let preset_keys: Vec<String> = self.preset_manager.keys().into_iter().cloned().collect();
+        let description = self
+            .preset_manager
+            .get(&self.selected_preset)
+            .and_then(|preset| preset.description.as_deref())
+            .unwrap_or_default();
 
         let preset_picker = row![
+            text(description).size(12),
             text(format!("{}:", s.style_preset)).size(13),
             pick_list(
-                preset_keys,
-                Some(self.selected_preset.clone()),
-                Message::PresetSelected
+                self.preset_options(),
+                Some(self.preset_option(self.selected_preset.clone())),
+                |option| Message::PresetSelected(option.key)
             )
             .placeholder(s.select_preset)
             .text_size(13),
@@ -600,6 +1359,7 @@ impl App {
             container(
                 text_editor(&self.input_content)
                     .on_action(Message::InputChanged)
+                    .key_binding(|key_press| self.editor_binding(key_press))
                     .height(Length::Fill)
             )
             .height(Length::FillPortion(1))
@@ -619,22 +1379,20 @@ impl App {
         .spacing(8);
 
         // Action buttons with modern styling
+        let check_label = if self.is_loading {
+            self.progress_label()
+        } else {
+            s.check_grammar.to_string()
+        };
         let action_buttons = row![
-            button(
-                text(if self.is_loading {
-                    s.processing
+            button(text(check_label).size(14))
+                .style(button::primary)
+                .padding(Padding::from([10, 20]))
+                .on_press_maybe(if self.is_loading {
+                    None
                 } else {
-                    s.check_grammar
-                })
-                .size(14)
-            )
-            .style(button::primary)
-            .padding(Padding::from([10, 20]))
-            .on_press_maybe(if self.is_loading {
-                None
-            } else {
-                Some(Message::CheckGrammar)
-            }),
+                    Some(Message::CheckGrammar)
+                }),
             button(
                 text(if self.is_loading {
                     s.processing
@@ -687,6 +1445,7 @@ impl App {
                         container(
                             text_editor(&self.result_text)
                                 .on_action(Message::ResultAction)
+                                .key_binding(|key_press| self.editor_binding(key_press))
                                 .padding(12)
                                 .height(Length::Fill),
                         )
@@ -713,6 +1472,7 @@ impl App {
             vertical_space().height(12),
             action_buttons,
             error_view,
+            self.budget_note(),
             vertical_space().height(12),
             result_area,
         ]
@@ -726,13 +1486,46 @@ impl App {
         let nav = row![
             self.nav_button(s.nav_main, View::Main),
             self.nav_button(s.nav_settings, View::Settings),
+            self.nav_button(s.nav_presets, View::Presets),
             self.nav_button(s.nav_help, View::Help),
+            horizontal_space(),
+            if let Some(ref msg) = self.clipboard_msg {
+                text(msg).size(13)
+            } else {
+                text("").size(13)
+            },
         ]
-        .spacing(8);
+        .spacing(8)
+        .align_y(iced::Alignment::Center);
+
+        let profile_options = self.profile_options();
+        let selected_profile = profile_options
+            .iter()
+            .find(|option| option.name == self.config.profile)
+            .cloned();
+        let profile_row: Element<Message> = if self.file_config.profiles.is_empty() {
+            column![].into()
+        } else {
+            row![
+                text(format!("{}:", s.profile)).size(13),
+                pick_list(profile_options, selected_profile, Message::ProfileSelected)
+                    .text_size(13),
+            ]
+            .spacing(8)
+            .align_y(iced::Alignment::Center)
+            .into()
+        };
+        let offline_note: Element<Message> = if self.config.is_offline() {
+            text(s.offline_profile).size(12).into()
+        } else {
+            column![].into()
+        };
 
         let api_section = column![
             text(s.api_config).size(18),
             vertical_space().height(8),
+            profile_row,
+            offline_note,
             text(s.enter_api_key).size(13),
             row![
                 text_input(s.api_key_placeholder, &self.api_key_input)
@@ -746,10 +1539,37 @@ impl App {
             ]
             .spacing(8),
             text(s.get_api_key).size(12),
+            row![
+                checkbox(s.cache_results, self.config.cache.enabled)
+                    .on_toggle(Message::CacheToggled)
+                    .text_size(13),
+                horizontal_space(),
+                button(text(s.clear_cache).size(13))
+                    .style(button::secondary)
+                    .on_press(Message::ClearCache),
+            ]
+            .spacing(8)
+            .align_y(iced::Alignment::Center),
+        ]
+        .spacing(8);
+
+        let mut month_line = self.usage_line(s.usage_month, &self.usage.month_total(&this_month()));
+        if self.config.usage.monthly_budget > 0.0 {
+            month_line.push_str(&s.usage_budget.replace(
+                "{budget}",
+                &format!("{:.2}", self.config.usage.monthly_budget),
+            ));
+        }
+        let usage_section = column![
+            text(s.usage).size(18),
+            vertical_space().height(8),
+            text(self.usage_line(s.usage_today, &self.usage.day_total(&today()))).size(13),
+            text(month_line).size(13),
+            self.budget_note(),
         ]
         .spacing(8);
 
-        let theme_options = vec!["dark".to_string(), "light".to_string()];
+        let theme_options: Vec<String> = THEMES.iter().map(|t| t.to_string()).collect();
         let lang_options = Language::all();
 
         let appearance_section = column![
@@ -785,26 +1605,245 @@ impl App {
             column![].into()
         };
 
+        let issues_view: Element<Message> = if self.config_issues.is_empty() {
+            column![].into()
+        } else {
+            let issues = self
+                .config_issues
+                .iter()
+                .fold(column![text(s.config_issues).size(14)], |col, issue| {
+                    col.push(text(format!("! {}", issue)).size(12))
+                })
+                .push(text(s.config_issues_hint).size(12))
+                .spacing(4);
+            container(issues)
+                .padding(12)
+                .style(container::bordered_box)
+                .width(Length::Fill)
+                .into()
+        };
+
         column![
             nav,
             vertical_space().height(24),
+            issues_view,
             api_section,
             vertical_space().height(24),
             horizontal_rule(1),
             vertical_space().height(24),
             appearance_section,
+            vertical_space().height(24),
+            horizontal_rule(1),
+            vertical_space().height(24),
+            usage_section,
             error_view,
         ]
         .spacing(8)
         .into()
     }
 
+    fn view_presets(&self) -> Element<'_, Message> {
+        let s = self.s();
+
+        let nav = row![
+            self.nav_button(s.nav_main, View::Main),
+            self.nav_button(s.nav_settings, View::Settings),
+            self.nav_button(s.nav_presets, View::Presets),
+            self.nav_button(s.nav_help, View::Help),
+            horizontal_space(),
+            if let Some(ref msg) = self.clipboard_msg {
+                text(msg).size(13)
+            } else {
+                text("").size(13)
+            },
+        ]
+        .spacing(8)
+        .align_y(iced::Alignment::Center);
+
+        let editing = self
+            .preset_draft
+            .as_ref()
+            .and_then(|draft| draft.original.as_deref());
+        let preset_list =
+            self.preset_manager
+                .keys()
+                .into_iter()
+                .fold(column![].spacing(4), |col, key| {
+                    let mut label = self.preset_label(key);
+                    if self.preset_manager.is_custom(key) {
+                        label.push_str(s.custom_marker);
+                    }
+                    if self.preset_manager.get(key).is_some_and(|p| p.hidden) {
+                        label.push_str(s.hidden_marker);
+                    }
+                    let style = if editing == Some(key.as_str()) {
+                        button::primary
+                    } else {
+                        button::secondary
+                    };
+                    col.push(
+                        button(text(label).size(13))
+                            .style(style)
+                            .width(Length::Fill)
+                            .on_press(Message::EditPreset(key.clone())),
+                    )
+                });
+        let list_column = column![
+            scrollable(preset_list).height(Length::Fill),
+            button(text(s.new_preset).size(13))
+                .style(button::success)
+                .on_press(Message::NewPreset),
+        ]
+        .spacing(8)
+        .width(Length::FillPortion(1));
+
+        let error_view: Element<Message> = if let Some(ref err) = self.error_message {
+            container(text(format!("! {}", err)).size(13))
+                .padding(12)
+                .style(container::bordered_box)
+                .width(Length::Fill)
+                .into()
+        } else {
+            column![].into()
+        };
+
+        let editor: Element<Message> = match self.preset_draft {
+            None => text(s.preset_select_hint).size(13).into(),
+            Some(ref draft) => {
+                let field = |label: &'static str, value: &str, field: PresetField| {
+                    column![
+                        text(label).size(12),
+                        text_input(label, value)
+                            .on_input(move |v| Message::PresetFieldChanged(field, v))
+                            .size(13),
+                    ]
+                    .spacing(4)
+                };
+                let dialect_options: Vec<DialectOption> = std::iter::once(None)
+                    .chain(Dialect::all().iter().copied().map(Some))
+                    .map(DialectOption)
+                    .collect();
+                let can_delete = draft
+                    .original
+                    .as_deref()
+                    .is_some_and(|key| self.preset_manager.is_custom(key));
+
+                let sample = self.input_content.text();
+                let sample = if sample.trim().is_empty() {
+                    s.preview_sample.to_string()
+                } else {
+                    sample
+                };
+                let preview = self.templates.enhance_prompt(
+                    &sample,
+                    &draft.preset(),
+                    self.language.display_name(),
+                );
+
+                column![
+                    field(s.preset_key, &draft.key, PresetField::Key),
+                    row![
+                        field(s.preset_name, &draft.name, PresetField::Name),
+                        field(s.preset_icon, &draft.icon, PresetField::Icon),
+                    ]
+                    .spacing(8),
+                    field(
+                        s.preset_description,
+                        &draft.description,
+                        PresetField::Description
+                    ),
+                    row![
+                        field(s.preset_tone, &draft.tone, PresetField::Tone),
+                        field(s.preset_formality, &draft.formality, PresetField::Formality),
+                    ]
+                    .spacing(8),
+                    text(s.preset_instructions).size(12),
+                    container(
+                        text_editor(&draft.instructions)
+                            .on_action(Message::PresetInstructionsAction)
+                            .key_binding(|key_press| self.editor_binding(key_press))
+                            .height(Length::Fill)
+                    )
+                    .height(100)
+                    .style(container::bordered_box),
+                    row![
+                        field(s.preset_audience, &draft.audience, PresetField::Audience),
+                        field(
+                            s.preset_target_length,
+                            &draft.target_length,
+                            PresetField::TargetLength
+                        ),
+                    ]
+                    .spacing(8),
+                    row![
+                        field(
+                            s.preset_banned_words,
+                            &draft.banned_words,
+                            PresetField::BannedWords
+                        ),
+                        column![
+                            text(s.preset_dialect).size(12),
+                            pick_list(
+                                dialect_options,
+                                Some(DialectOption(draft.dialect)),
+                                Message::PresetDialectChanged
+                            )
+                            .text_size(13),
+                        ]
+                        .spacing(4),
+                    ]
+                    .spacing(8),
+                    row![
+                        field(s.preset_order, &draft.order, PresetField::Order),
+                        checkbox(s.preset_hidden, draft.hidden)
+                            .on_toggle(Message::PresetHiddenToggled)
+                            .text_size(13),
+                    ]
+                    .spacing(8)
+                    .align_y(iced::Alignment::End),
+                    row![
+                        button(text(s.save).size(13))
+                            .style(button::primary)
+                            .on_press(Message::SavePreset),
+                        button(text(s.duplicate_preset).size(13))
+                            .style(button::secondary)
+                            .on_press(Message::DuplicatePreset),
+                        button(text(s.delete_preset).size(13))
+                            .style(button::danger)
+                            .on_press_maybe(can_delete.then_some(Message::DeletePreset)),
+                    ]
+                    .spacing(8),
+                    error_view,
+                    text(s.prompt_preview).size(14),
+                    container(scrollable(text(preview.to_string()).size(12)).height(Length::Fill))
+                        .padding(8)
+                        .height(Length::Fill)
+                        .width(Length::Fill)
+                        .style(container::bordered_box),
+                ]
+                .spacing(8)
+                .into()
+            }
+        };
+
+        column![
+            nav,
+            vertical_space().height(16),
+            row![list_column, container(editor).width(Length::FillPortion(3)),]
+                .spacing(16)
+                .height(Length::Fill),
+        ]
+        .spacing(8)
+        .into()
+    }
+
     fn view_help(&self) -> Element<'_, Message> {
         let s = self.s();
 
         let nav = row![
             self.nav_button(s.nav_main, View::Main),
             self.nav_button(s.nav_settings, View::Settings),
+            self.nav_button(s.nav_presets, View::Presets),
             self.nav_button(s.nav_help, View::Help),
             horizontal_space(),
             if let Some(ref msg) = self.clipboard_msg {
@@ -863,6 +1902,26 @@ impl App {
         ]
         .spacing(4);
 
+        let keys = &self.config.keybindings;
+        let key_row = |combo: &str, label: &'static str| -> Element<'_, Message> {
+            text(format!("{:<12} {}", combo, label)).size(13).into()
+        };
+        let keys_section = column![
+            text(s.keys_title).size(18),
+            vertical_space().height(8),
+            key_row(&keys.check, s.keys_check),
+            key_row(&keys.enhance, s.keys_enhance),
+            key_row(&keys.copy_result, s.keys_copy_result),
+            key_row(&keys.close, s.keys_close),
+            key_row(
+                &format!("{} / {}", keys.next_issue, keys.previous_issue),
+                s.keys_cycle_issues
+            ),
+            key_row(&keys.presets.join(" "), s.keys_presets),
+            text(s.keys_config_note).size(12),
+        ]
+        .spacing(4);
+
         let preset_section = column![
             text(s.preset_guide).size(18),
             vertical_space().height(8),
@@ -881,6 +1940,10 @@ impl App {
                 vertical_space().height(24),
                 horizontal_rule(1),
                 vertical_space().height(24),
+                keys_section,
+                vertical_space().height(24),
+                horizontal_rule(1),
+                vertical_space().height(24),
                 preset_section,
                 vertical_space().height(24),
                 horizontal_rule(1),
@@ -918,6 +1981,7 @@ impl App {
                         container(
                             text_editor(&self.result_text)
                                 .on_action(Message::ResultAction)
+                                .key_binding(|key_press| self.editor_binding(key_press))
                                 .padding(8)
                                 .height(Length::Fill)
                         )
@@ -944,6 +2008,7 @@ impl App {
             container(
                 text_editor(&self.input_content)
                     .on_action(Message::InputChanged)
+                    .key_binding(|key_press| self.editor_binding(key_press))
                     .height(Length::Fixed(100.0))
             )
             .style(container::bordered_box),
@@ -951,13 +2016,9 @@ impl App {
                 // Compact preset picker
                 text(format!("{}:", s.style_preset)).size(12),
                 pick_list(
-                    self.preset_manager
-                        .keys()
-                        .into_iter()
-                        .cloned()
-                        .collect::<Vec<_>>(),
-                    Some(self.selected_preset.clone()),
-                    Message::PresetSelected
+                    self.preset_options(),
+                    Some(self.preset_option(self.selected_preset.clone())),
+                    |option| Message::PresetSelected(option.key)
                 )
                 .text_size(12)
                 .padding(4),
@@ -973,23 +2034,21 @@ impl App {
         .spacing(4);
 
         // Compact actions
+        let check_label = if self.is_loading {
+            self.progress_label()
+        } else {
+            s.check_grammar.to_string()
+        };
         let action_buttons = row![
-            button(
-                text(if self.is_loading {
-                    s.processing
+            button(text(check_label).size(13))
+                .style(button::primary)
+                .width(Length::Fill)
+                .padding(Padding::from([8, 0]))
+                .on_press_maybe(if self.is_loading {
+                    None
                 } else {
-                    s.check_grammar
-                })
-                .size(13)
-            )
-            .style(button::primary)
-            .width(Length::Fill)
-            .padding(Padding::from([8, 0]))
-            .on_press_maybe(if self.is_loading {
-                None
-            } else {
-                Some(Message::CheckGrammar)
-            }),
+                    Some(Message::CheckGrammar)
+                }),
             button(
                 text(if self.is_loading {
                     s.processing
@@ -1020,36 +2079,116 @@ impl App {
             column![].into()
         };
 
-        column![input_area, action_buttons, error_view, result_area,]
-            .spacing(8)
-            .into()
+        column![
+            input_area,
+            action_buttons,
+            error_view,
+            self.budget_note(),
+            result_area,
+        ]
+        .spacing(8)
+        .into()
     }
 }
 
-// Helper to get clipboard text, prioritizing wl-paste on Linux
-fn get_clipboard_text() -> Option<String> {
-    // 1. Try wl-paste on Linux first
-    #[cfg(target_os = "linux")]
-    {
-        use std::process::Command;
+/// Key shown in the settings field: the one `SaveApiKey` writes to, never
+/// an environment override
+fn file_api_key(file_config: &AppConfig, profile: &str) -> String {
+    file_config
+        .profiles
+        .get(profile)
+        .and_then(|p| p.api.as_ref())
+        .unwrap_or(&file_config.api)
+        .gemini_key
+        .clone()
+}
 
-        let output = Command::new("wl-paste").arg("--no-newline").output();
+/// Modification times of config.toml, presets.toml and the prompt templates
+type ConfigStamp = [Option<SystemTime>; 4];
+
+/// Read the current modification times of the config files
+fn config_stamp() -> ConfigStamp {
+    let modified = |path: Option<PathBuf>| {
+        path.and_then(|p| std::fs::metadata(p).ok())
+            .and_then(|m| m.modified().ok())
+    };
+    let prompt = |kind: PromptKind| prompts_dir().map(|dir| dir.join(kind.file_name()));
+    [
+        modified(config_path()),
+        modified(presets_path()),
+        modified(prompt(PromptKind::Check)),
+        modified(prompt(PromptKind::Enhance)),
+    ]
+}
 
-        if let Ok(output) = output {
-            if output.status.success() {
-                if let Ok(text) = String::from_utf8(output.stdout) {
-                    return Some(text);
-                }
+/// Report changes in the directory of the config files
+///
+/// The directory is watched recursively, which covers the prompt templates
+/// even if `prompts/` is created later.
+fn watch_config_files() -> impl iced::futures::Stream<Item = Message> {
+    iced::stream::channel(1, |mut output| async move {
+        use iced::futures::StreamExt;
+        use notify::Watcher;
+
+        let Some(dir) = config_path().and_then(|path| path.parent().map(PathBuf::from)) else {
+            return;
+        };
+        let (changes, mut changed) = iced::futures::channel::mpsc::unbounded();
+        let watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+            // Reading the files ourselves shows up as access events
+            if event.is_ok_and(|event| !event.kind.is_access()) {
+                let _ = changes.unbounded_send(());
             }
+        });
+        // The directory doesn't exist before the first save
+        let _ = std::fs::create_dir_all(&dir);
+        let mut watcher = match watcher {
+            Ok(watcher) => watcher,
+            Err(e) => {
+                tracing::warn!("Config files are not reloaded on change: {}", e);
+                return;
+            }
+        };
+        if let Err(e) = watcher.watch(&dir, notify::RecursiveMode::Recursive) {
+            tracing::warn!("Failed to watch {}: {}", dir.display(), e);
+            return;
         }
-    }
 
-    // 2. Fallback to arboard
-    if let Ok(mut clipboard) = arboard::Clipboard::new() {
-        if let Ok(text) = clipboard.get_text() {
-            return Some(text);
+        while changed.next().await.is_some() {
+            // Editors save in several steps; report them as one change
+            tokio::time::sleep(CONFIG_SETTLE_DELAY).await;
+            while let Ok(Some(())) = changed.try_next() {}
+            if output.send(Message::ConfigFilesChanged).await.is_err() {
+                break;
+            }
         }
+    })
+}
+
+/// Map window events to overlay messages
+fn overlay_event(
+    event: iced::Event,
+    _status: iced::event::Status,
+    _window: window::Id,
+) -> Option<Message> {
+    match event {
+        iced::Event::Window(window::Event::Opened { .. }) => Some(Message::OverlayOpened),
+        iced::Event::Window(window::Event::Focused) => Some(Message::OverlayFocusChanged(true)),
+        iced::Event::Window(window::Event::Unfocused) => Some(Message::OverlayFocusChanged(false)),
+        _ => None,
     }
+}
 
-    None
+/// Forward key presses to the shortcut handler
+fn key_event(
+    event: iced::Event,
+    status: iced::event::Status,
+    _window: window::Id,
+) -> Option<Message> {
+    match event {
+        iced::Event::Keyboard(keyboard::Event::KeyPressed { key, modifiers, .. }) => {
+            Some(Message::KeyPressed(key, modifiers, status))
+        }
+        _ => None,
+    }
 }
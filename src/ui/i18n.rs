@@ -25,22 +25,25 @@ impl Language {
         }
     }
 
-    pub fn all() -> Vec<Language> {
-        vec![Language::Chinese, Language::English]
+    /// Value of `preferences.language` for this language
+    pub fn config_name(&self) -> &'static str {
+        match self {
+            Language::Chinese => "chinese",
+            Language::English => "english",
+        }
     }
-}
 
-impl Strings {
-    /// Get localized display name for a preset key
-    pub fn preset_display_name(&self, key: &str) -> String {
-        match key {
-            "casual" => self.preset_name_casual.to_string(),
-            "business" => self.preset_name_business.to_string(),
-            "academic" => self.preset_name_academic.to_string(),
-            "creative" => self.preset_name_creative.to_string(),
-            _ => key.to_string(),
+    /// Parse the `preferences.language` config value
+    pub fn from_config(value: &str) -> Self {
+        match value {
+            "english" => Language::English,
+            _ => Language::Chinese,
         }
     }
+
+    pub fn all() -> Vec<Language> {
+        vec![Language::Chinese, Language::English]
+    }
 }
 
 impl std::fmt::Display for Language {
@@ -55,6 +58,7 @@ pub struct Strings {
     pub nav_main: &'static str,
     pub nav_settings: &'static str,
     pub nav_help: &'static str,
+    pub nav_presets: &'static str,
 
     // Main view
     pub current_style: &'static str,
@@ -68,6 +72,11 @@ pub struct Strings {
     pub paste: &'static str,
     pub clear: &'static str,
     pub processing: &'static str,
+    /// Check progress of a long text; `{done}` and `{total}` are replaced
+    pub checked_parts: &'static str,
+    /// Shown after a check without an API key
+    pub rules_only: &'static str,
+    pub rules_only_offline: &'static str,
     pub check_grammar: &'static str,
     pub enhance_text: &'static str,
     pub result: &'static str,
@@ -86,10 +95,64 @@ pub struct Strings {
     pub api_not_configured: &'static str,
     pub invalid_preset: &'static str,
     pub error_prefix: &'static str,
+    pub unchecked_parts: &'static str,
     pub save_failed: &'static str,
+    pub config_not_saved: &'static str,
+
+    // Result cache
+    pub cache_results: &'static str,
+    pub clear_cache: &'static str,
+    pub cache_cleared: &'static str,
+
+    // Usage
+    pub usage: &'static str,
+    pub usage_today: &'static str,
+    pub usage_month: &'static str,
+    /// `{label}`, `{requests}`, `{prompt}` and `{output}` are replaced
+    pub usage_summary: &'static str,
+    /// Appended to the month's usage; `{budget}` is replaced
+    pub usage_budget: &'static str,
+    pub budget_exceeded: &'static str,
+
+    // Preset editor
+    pub new_preset: &'static str,
+    pub duplicate_preset: &'static str,
+    pub delete_preset: &'static str,
+    pub preset_key: &'static str,
+    pub preset_name: &'static str,
+    pub preset_tone: &'static str,
+    pub preset_formality: &'static str,
+    pub preset_instructions: &'static str,
+    pub preset_audience: &'static str,
+    pub preset_target_length: &'static str,
+    pub preset_dialect: &'static str,
+    pub preset_banned_words: &'static str,
+    pub prompt_preview: &'static str,
+    pub preview_sample: &'static str,
+    pub preset_saved: &'static str,
+    pub preset_deleted: &'static str,
+    pub preset_invalid_key: &'static str,
+    pub preset_missing_name: &'static str,
+    pub preset_exists: &'static str,
+    pub preset_select_hint: &'static str,
+    pub custom_marker: &'static str,
+    pub hidden_marker: &'static str,
+    pub preset_description: &'static str,
+    pub preset_icon: &'static str,
+    pub preset_order: &'static str,
+    pub preset_hidden: &'static str,
+    pub preset_invalid_order: &'static str,
+
+    // Notifications
+    pub notify_copied: &'static str,
+    pub notify_open_details: &'static str,
+    pub notify_undo_copy: &'static str,
 
     // Settings
     pub api_config: &'static str,
+    pub profile: &'static str,
+    pub no_profile: &'static str,
+    pub offline_profile: &'static str,
     pub enter_api_key: &'static str,
     pub api_key_placeholder: &'static str,
     pub save: &'static str,
@@ -97,6 +160,8 @@ pub struct Strings {
     pub appearance: &'static str,
     pub theme: &'static str,
     pub language: &'static str,
+    pub config_issues: &'static str,
+    pub config_issues_hint: &'static str,
 
     // Help
     pub shortcut_title: &'static str,
@@ -114,17 +179,19 @@ pub struct Strings {
     pub usage_step3: &'static str,
     pub usage_step4: &'static str,
     pub usage_step5: &'static str,
+    pub keys_title: &'static str,
+    pub keys_check: &'static str,
+    pub keys_enhance: &'static str,
+    pub keys_copy_result: &'static str,
+    pub keys_close: &'static str,
+    pub keys_cycle_issues: &'static str,
+    pub keys_presets: &'static str,
+    pub keys_config_note: &'static str,
     pub preset_guide: &'static str,
     pub preset_casual: &'static str,
     pub preset_business: &'static str,
     pub preset_academic: &'static str,
     pub preset_creative: &'static str,
-
-    // Preset display names for dropdown
-    pub preset_name_casual: &'static str,
-    pub preset_name_business: &'static str,
-    pub preset_name_academic: &'static str,
-    pub preset_name_creative: &'static str,
 }
 
 const CHINESE: Strings = Strings {
@@ -132,6 +199,7 @@ const CHINESE: Strings = Strings {
     nav_main: "主页",
     nav_settings: "设置",
     nav_help: "帮助",
+    nav_presets: "预设",
 
     // Main view
     current_style: "当前风格",
@@ -145,6 +213,9 @@ const CHINESE: Strings = Strings {
     paste: "[粘贴]",
     clear: "[清空]",
     processing: "处理中...",
+    checked_parts: "检查中 {done}/{total}",
+    rules_only: "仅使用内置规则检查；配置 API 密钥后可进行完整检查",
+    rules_only_offline: "离线模式下仅使用内置规则检查",
     check_grammar: "[检查语法]",
     enhance_text: "[润色文本]",
     result: "结果:",
@@ -163,10 +234,62 @@ const CHINESE: Strings = Strings {
     api_not_configured: "API 密钥未配置，请前往设置",
     invalid_preset: "无效的风格预设",
     error_prefix: "错误",
+    unchecked_parts: "部分内容未能检查，已保持原样：",
     save_failed: "保存配置失败",
+    config_not_saved: "config.toml 无法解析，修复之前不会保存设置",
+
+    // Result cache
+    cache_results: "缓存结果（相同文本不重复请求 API）",
+    clear_cache: "[清除缓存]",
+    cache_cleared: "缓存已清除",
+
+    // Usage
+    usage: "用量",
+    usage_today: "今日",
+    usage_month: "本月",
+    usage_summary: "{label}：{requests} 次请求 · 输入 {prompt} tokens · 输出 {output} tokens",
+    usage_budget: "（预算 ${budget}）",
+    budget_exceeded: "本月预算已用完",
+
+    // Preset editor
+    new_preset: "[新建]",
+    duplicate_preset: "[复制]",
+    delete_preset: "[删除]",
+    preset_key: "标识（字母、数字、- 和 _）",
+    preset_name: "名称",
+    preset_tone: "语气",
+    preset_formality: "正式程度",
+    preset_instructions: "说明",
+    preset_audience: "读者（可选）",
+    preset_target_length: "目标长度（可选）",
+    preset_dialect: "英语变体",
+    preset_banned_words: "禁用词（逗号分隔）",
+    prompt_preview: "提示词预览",
+    preview_sample: "（您的文本）",
+    preset_saved: "预设已保存到 presets.toml",
+    preset_deleted: "预设已删除",
+    preset_invalid_key: "标识只能包含字母、数字、- 和 _",
+    preset_missing_name: "请输入预设名称",
+    preset_exists: "已存在同名标识的预设",
+    preset_select_hint: "在左侧选择一个预设进行编辑，或新建预设",
+    custom_marker: "（自定义）",
+    hidden_marker: "（隐藏）",
+    preset_description: "简介（可选）",
+    preset_icon: "图标（可选）",
+    preset_order: "排序（数字越小越靠前）",
+    preset_hidden: "在选择列表中隐藏",
+    preset_invalid_order: "排序必须是整数",
+
+    // Notifications
+    notify_copied: "修正后的文本已复制到剪贴板",
+    notify_open_details: "查看详情",
+    notify_undo_copy: "撤销复制",
 
     // Settings
     api_config: "API 配置",
+    profile: "配置方案",
+    no_profile: "(无)",
+    offline_profile: "离线模式：不会发送任何文本",
     enter_api_key: "请输入您的 Gemini API 密钥:",
     api_key_placeholder: "API 密钥",
     save: "[保存]",
@@ -174,6 +297,8 @@ const CHINESE: Strings = Strings {
     appearance: "外观设置",
     theme: "主题",
     language: "语言",
+    config_issues: "配置问题",
+    config_issues_hint: "以上设置已使用默认值，可运行 akkurate config check 重新检查",
 
     // Help
     shortcut_title: "快捷键设置",
@@ -191,15 +316,19 @@ const CHINESE: Strings = Strings {
     usage_step3: "3. 自动检查语法并显示结果",
     usage_step4: "4. 或使用主界面输入/粘贴文本进行检查",
     usage_step5: "5. 复制结果到其他地方使用",
+    keys_title: "窗口快捷键",
+    keys_check: "检查语法",
+    keys_enhance: "润色文本",
+    keys_copy_result: "复制结果",
+    keys_close: "关闭弹窗",
+    keys_cycle_issues: "切换问题",
+    keys_presets: "选择文风预设",
+    keys_config_note: "可在 config.toml 的 [keybindings] 中修改",
     preset_guide: "文风预设说明",
     preset_casual: "casual（日常）: 友好随意，适合聊天、社交媒体",
     preset_business: "business（商务）: 专业礼貌，适合邮件、报告",
     preset_academic: "academic（学术）: 正式严谨，适合论文、文档",
     preset_creative: "creative（创意）: 生动表达，适合故事、博客",
-    preset_name_casual: "日常",
-    preset_name_business: "商务",
-    preset_name_academic: "学术",
-    preset_name_creative: "创意",
 };
 
 const ENGLISH: Strings = Strings {
@@ -207,6 +336,7 @@ const ENGLISH: Strings = Strings {
     nav_main: "Main",
     nav_settings: "Settings",
     nav_help: "Help",
+    nav_presets: "Presets",
 
     // Main view
     current_style: "Current Style",
@@ -220,6 +350,9 @@ const ENGLISH: Strings = Strings {
     paste: "[Paste]",
     clear: "[Clear]",
     processing: "Processing...",
+    checked_parts: "Checking {done}/{total}",
+    rules_only: "Checked with built-in rules only; add an API key for a full check",
+    rules_only_offline: "Offline mode is on; checked with built-in rules only",
     check_grammar: "[Check Grammar]",
     enhance_text: "[Enhance Text]",
     result: "Result:",
@@ -238,10 +371,62 @@ const ENGLISH: Strings = Strings {
     api_not_configured: "API key not configured. Go to Settings.",
     invalid_preset: "Invalid preset selected",
     error_prefix: "Error",
+    unchecked_parts: "Some parts could not be checked and were left as they are:",
     save_failed: "Failed to save config",
+    config_not_saved: "config.toml doesn't parse; settings are not saved until it is fixed",
+
+    // Result cache
+    cache_results: "Cache results (don't resend identical text)",
+    clear_cache: "[Clear Cache]",
+    cache_cleared: "Cache cleared",
+
+    // Usage
+    usage: "Usage",
+    usage_today: "Today",
+    usage_month: "This month",
+    usage_summary: "{label}: {requests} requests · {prompt} prompt tokens · {output} output tokens",
+    usage_budget: " (budget ${budget})",
+    budget_exceeded: "The monthly budget is used up",
+
+    // Preset editor
+    new_preset: "[New]",
+    duplicate_preset: "[Duplicate]",
+    delete_preset: "[Delete]",
+    preset_key: "Key (letters, digits, - and _)",
+    preset_name: "Name",
+    preset_tone: "Tone",
+    preset_formality: "Formality",
+    preset_instructions: "Instructions",
+    preset_audience: "Audience (optional)",
+    preset_target_length: "Target length (optional)",
+    preset_dialect: "Dialect",
+    preset_banned_words: "Banned words (comma-separated)",
+    prompt_preview: "Prompt preview",
+    preview_sample: "(your text)",
+    preset_saved: "Preset saved to presets.toml",
+    preset_deleted: "Preset deleted",
+    preset_invalid_key: "Keys may only contain letters, digits, - and _",
+    preset_missing_name: "Please enter a preset name",
+    preset_exists: "A preset with this key already exists",
+    preset_select_hint: "Select a preset on the left to edit it, or create a new one",
+    custom_marker: " (custom)",
+    hidden_marker: " (hidden)",
+    preset_description: "Description (optional)",
+    preset_icon: "Icon (optional)",
+    preset_order: "Order (lower comes first)",
+    preset_hidden: "Hide from pickers",
+    preset_invalid_order: "Order must be a whole number",
+
+    // Notifications
+    notify_copied: "Corrected text copied to clipboard",
+    notify_open_details: "Open details",
+    notify_undo_copy: "Undo copy",
 
     // Settings
     api_config: "API Configuration",
+    profile: "Profile",
+    no_profile: "(none)",
+    offline_profile: "Offline mode: no text is sent anywhere",
     enter_api_key: "Enter your Gemini API key:",
     api_key_placeholder: "API Key",
     save: "[Save]",
@@ -249,6 +434,9 @@ const ENGLISH: Strings = Strings {
     appearance: "Appearance",
     theme: "Theme",
     language: "Language",
+    config_issues: "Configuration problems",
+    config_issues_hint:
+        "Defaults are used for these settings. Run `akkurate config check` to re-check.",
 
     // Help
     shortcut_title: "Keyboard Shortcuts Setup",
@@ -266,13 +454,17 @@ const ENGLISH: Strings = Strings {
     usage_step3: "3. Grammar is auto-checked and results are shown",
     usage_step4: "4. Or use the main interface to input/paste text",
     usage_step5: "5. Copy the results to use elsewhere",
+    keys_title: "Window Shortcuts",
+    keys_check: "Check grammar",
+    keys_enhance: "Enhance text",
+    keys_copy_result: "Copy result",
+    keys_close: "Close popup",
+    keys_cycle_issues: "Cycle issues",
+    keys_presets: "Pick style preset",
+    keys_config_note: "Change them under [keybindings] in config.toml",
     preset_guide: "Style Presets",
     preset_casual: "casual: Friendly, conversational - for chat, social media",
     preset_business: "business: Professional, polite - for emails, reports",
     preset_academic: "academic: Formal, rigorous - for papers, documentation",
     preset_creative: "creative: Expressive, vivid - for stories, blogs",
-    preset_name_casual: "Casual",
-    preset_name_business: "Business",
-    preset_name_academic: "Academic",
-    preset_name_creative: "Creative",
 };
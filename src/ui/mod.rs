@@ -2,8 +2,12 @@
 
 pub mod app;
 pub mod i18n;
+pub mod notification;
+pub mod overlay;
+pub mod shortcuts;
 pub mod theme;
 
 pub use app::App;
 pub use app::AppFlags;
+pub use app::WINDOW_TITLE;
 pub use i18n::Language;
@@ -0,0 +1,95 @@
+//! Desktop notifications for results when no window is shown
+//!
+//! Uses the freedesktop `org.freedesktop.Notifications` service on the
+//! session bus.
+
+use crate::api::CheckResult;
+use crate::ui::i18n::Language;
+use anyhow::Result;
+
+/// Action identifier for opening the result window
+const ACTION_DETAILS: &str = "details";
+
+/// Action identifier for restoring the previous clipboard content
+const ACTION_UNDO: &str = "undo";
+
+/// Longest preview of the corrected text shown in the notification body
+const PREVIEW_CHARS: usize = 200;
+
+/// What the user did with a result notification
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NotificationAction {
+    OpenDetails,
+    UndoCopy,
+    Dismissed,
+}
+
+/// Show a summary of a check result and wait for the user to act on it
+#[cfg(target_os = "linux")]
+pub fn notify_check_result(result: &CheckResult, language: Language) -> Result<NotificationAction> {
+    let s = language.strings();
+
+    let summary = if result.issues.is_empty() {
+        s.no_issues.to_string()
+    } else {
+        s.found_issues
+            .replace("{}", &result.issues.len().to_string())
+    };
+    let body = format!("{}\n\n{}", preview(&result.corrected_text), s.notify_copied);
+
+    let handle = notify_rust::Notification::new()
+        .appname("Akkurate")
+        .summary(&summary)
+        .body(&body)
+        .icon("accessories-text-editor")
+        .action(ACTION_DETAILS, s.notify_open_details)
+        .action(ACTION_UNDO, s.notify_undo_copy)
+        .show()?;
+
+    let mut action = NotificationAction::Dismissed;
+    handle.wait_for_action(|id| {
+        action = match id {
+            // "default" is sent when the notification body itself is clicked
+            ACTION_DETAILS | "default" => NotificationAction::OpenDetails,
+            ACTION_UNDO => NotificationAction::UndoCopy,
+            _ => NotificationAction::Dismissed,
+        }
+    });
+    Ok(action)
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn notify_check_result(
+    _result: &CheckResult,
+    _language: Language,
+) -> Result<NotificationAction> {
+    anyhow::bail!("Desktop notifications are only supported on Linux")
+}
+
+/// Show an error notification without waiting for a response
+#[cfg(target_os = "linux")]
+pub fn notify_error(message: &str, language: Language) -> Result<()> {
+    notify_rust::Notification::new()
+        .appname("Akkurate")
+        .summary(language.strings().error_prefix)
+        .body(message)
+        .icon("dialog-error")
+        .show()?;
+    Ok(())
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn notify_error(_message: &str, _language: Language) -> Result<()> {
+    anyhow::bail!("Desktop notifications are only supported on Linux")
+}
+
+/// Shorten text for the notification body
+fn preview(text: &str) -> String {
+    if text.chars().count() <= PREVIEW_CHARS {
+        text.to_string()
+    } else {
+        let mut preview: String = text.chars().take(PREVIEW_CHARS).collect();
+        preview.push('…');
+        preview
+    }
+}
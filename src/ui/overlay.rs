@@ -0,0 +1,253 @@
+//! Overlay popup placed next to the pointer
+//!
+//! This is not a wlr-layer-shell surface: iced 0.13 can only create regular
+//! toplevel windows. The overlay is an undecorated, always-on-top window that
+//! is moved next to the pointer where the session allows it:
+//!
+//! - Hyprland and Sway: floated and moved through the compositor's IPC once mapped
+//! - X11: the initial window position, from `xdotool`
+//!
+//! Near a screen edge the overlay is kept on the monitor under the pointer;
+//! Sway does this itself for `move position mouse`. Other Wayland
+//! compositors place it like any other window.
+
+use std::process::{Command, Stdio};
+
+/// Application id (Wayland) / class (X11) of the overlay window
+pub const APP_ID: &str = "akkurate-overlay";
+
+/// Overlay window size
+pub const SIZE: (f32, f32) = (460.0, 380.0);
+
+/// Distance between the pointer and the overlay's top-left corner
+const POINTER_OFFSET: f32 = 12.0;
+
+/// Session compositor, as far as placement is concerned
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compositor {
+    Hyprland,
+    Sway,
+    X11,
+    Other,
+}
+
+impl Compositor {
+    /// Detect the compositor from the session environment
+    pub fn detect() -> Self {
+        let is_set = |name: &str| std::env::var_os(name).is_some_and(|v| !v.is_empty());
+
+        if is_set("HYPRLAND_INSTANCE_SIGNATURE") {
+            Compositor::Hyprland
+        } else if is_set("SWAYSOCK") {
+            Compositor::Sway
+        } else if !is_set("WAYLAND_DISPLAY") && is_set("DISPLAY") {
+            Compositor::X11
+        } else {
+            Compositor::Other
+        }
+    }
+}
+
+/// Get the pointer position in global coordinates, where the session exposes it
+pub fn cursor_position(compositor: Compositor) -> Option<(f32, f32)> {
+    match compositor {
+        // "1234, 567"
+        Compositor::Hyprland => {
+            let output = command_output("hyprctl", &["cursorpos"])?;
+            let (x, y) = output.trim().split_once(',')?;
+            Some((x.trim().parse().ok()?, y.trim().parse().ok()?))
+        }
+        // "X=1234\nY=567\nSCREEN=0\nWINDOW=..."
+        Compositor::X11 => {
+            let output = command_output("xdotool", &["getmouselocation", "--shell"])?;
+            let value = |key: &str| {
+                output
+                    .lines()
+                    .find_map(|line| line.strip_prefix(key))
+                    .and_then(|v| v.trim().parse().ok())
+            };
+            Some((value("X=")?, value("Y=")?))
+        }
+        Compositor::Sway | Compositor::Other => None,
+    }
+}
+
+/// Monitor area in global coordinates
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Bounds {
+    x: f32,
+    y: f32,
+    width: f32,
+    height: f32,
+}
+
+/// Get the area of the monitor containing `point`, where the session exposes it
+fn monitor_bounds(compositor: Compositor, point: (f32, f32)) -> Option<Bounds> {
+    match compositor {
+        Compositor::Hyprland => {
+            let output = command_output("hyprctl", &["monitors", "-j"])?;
+            let monitors: Vec<serde_json::Value> = serde_json::from_str(&output).ok()?;
+            monitors.iter().find_map(|monitor| {
+                let number = |key: &str| monitor.get(key)?.as_f64().map(|v| v as f32);
+                let scale = number("scale").filter(|s| *s > 0.0).unwrap_or(1.0);
+                let (mut width, mut height) = (number("width")? / scale, number("height")? / scale);
+                // Odd transforms are rotated by 90 or 270 degrees
+                if number("transform").is_some_and(|t| t as i32 % 2 == 1) {
+                    std::mem::swap(&mut width, &mut height);
+                }
+                let bounds = Bounds {
+                    x: number("x")?,
+                    y: number("y")?,
+                    width,
+                    height,
+                };
+                bounds.contains(point).then_some(bounds)
+            })
+        }
+        // "1920 1080", the whole X screen
+        Compositor::X11 => {
+            let output = command_output("xdotool", &["getdisplaygeometry"])?;
+            let (width, height) = output.trim().split_once(' ')?;
+            Some(Bounds {
+                x: 0.0,
+                y: 0.0,
+                width: width.trim().parse().ok()?,
+                height: height.trim().parse().ok()?,
+            })
+        }
+        Compositor::Sway | Compositor::Other => None,
+    }
+}
+
+impl Bounds {
+    fn contains(&self, (x, y): (f32, f32)) -> bool {
+        x >= self.x && x < self.x + self.width && y >= self.y && y < self.y + self.height
+    }
+}
+
+/// Top-left corner of the overlay next to the pointer, kept inside `bounds`
+///
+/// Where there is no room to the right or below, the overlay opens to the
+/// left or above the pointer instead.
+fn overlay_position(pointer: (f32, f32), bounds: Option<Bounds>) -> (f32, f32) {
+    let (x, y) = (pointer.0 + POINTER_OFFSET, pointer.1 + POINTER_OFFSET);
+    let Some(bounds) = bounds else {
+        return (x, y);
+    };
+    let fit = |start: f32, pointer: f32, size: f32, min: f32, extent: f32| {
+        let max = min + extent - size;
+        if start <= max {
+            start
+        } else {
+            (pointer - POINTER_OFFSET - size).clamp(min, max.max(min))
+        }
+    };
+    (
+        fit(x, pointer.0, SIZE.0, bounds.x, bounds.width),
+        fit(y, pointer.1, SIZE.1, bounds.y, bounds.height),
+    )
+}
+
+/// Overlay position next to the pointer on the current monitor
+fn position_near_cursor(compositor: Compositor) -> Option<(f32, f32)> {
+    let pointer = cursor_position(compositor)?;
+    Some(overlay_position(
+        pointer,
+        monitor_bounds(compositor, pointer),
+    ))
+}
+
+/// Initial window position for the overlay
+///
+/// Only X11 honours client-side positions; Wayland windows are moved by
+/// [`place_near_cursor`] once they are mapped.
+pub fn initial_position() -> Option<(f32, f32)> {
+    match Compositor::detect() {
+        Compositor::X11 => position_near_cursor(Compositor::X11),
+        _ => None,
+    }
+}
+
+/// Float the mapped overlay window and move it next to the pointer
+pub fn place_near_cursor() {
+    match Compositor::detect() {
+        Compositor::Hyprland => {
+            let Some((x, y)) = position_near_cursor(Compositor::Hyprland) else {
+                return;
+            };
+            let window = format!("class:^({})$", APP_ID);
+            let batch = format!(
+                "dispatch setfloating {window} ; dispatch movewindowpixel exact {} {},{window}",
+                x as i32, y as i32,
+            );
+            run("hyprctl", &["--batch", &batch]);
+        }
+        Compositor::Sway => {
+            let command = format!(
+                "[app_id=\"{}\"] floating enable, sticky enable, border none, move position mouse",
+                APP_ID
+            );
+            run("swaymsg", &[&command]);
+        }
+        Compositor::Other => {
+            tracing::info!("This compositor can't be asked to move the overlay; it is placed like a normal window");
+        }
+        Compositor::X11 => {}
+    }
+}
+
+fn command_output(program: &str, args: &[&str]) -> Option<String> {
+    Command::new(program)
+        .args(args)
+        .stderr(Stdio::null())
+        .output()
+        .ok()
+        .filter(|o| o.status.success())
+        .and_then(|o| String::from_utf8(o.stdout).ok())
+}
+
+fn run(program: &str, args: &[&str]) {
+    if command_output(program, args).is_none() {
+        tracing::warn!("Failed to place overlay with {}", program);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCREEN: Bounds = Bounds {
+        x: 1920.0,
+        y: 0.0,
+        width: 1280.0,
+        height: 720.0,
+    };
+
+    #[test]
+    fn overlay_stays_on_the_monitor() {
+        let offset = POINTER_OFFSET;
+        assert_eq!(
+            overlay_position((2000.0, 100.0), Some(SCREEN)),
+            (2000.0 + offset, 100.0 + offset)
+        );
+        // Bottom-right corner: opens to the left of and above the pointer
+        assert_eq!(
+            overlay_position((3190.0, 710.0), Some(SCREEN)),
+            (3190.0 - offset - SIZE.0, 710.0 - offset - SIZE.1)
+        );
+        // No room either way: pressed against the monitor edge
+        let tiny = Bounds {
+            width: 500.0,
+            height: 400.0,
+            ..SCREEN
+        };
+        assert_eq!(
+            overlay_position((2150.0, 200.0), Some(tiny)),
+            (SCREEN.x, SCREEN.y)
+        );
+        assert_eq!(
+            overlay_position((5.0, 5.0), None),
+            (5.0 + offset, 5.0 + offset)
+        );
+    }
+}
@@ -0,0 +1,243 @@
+//! Keyboard shortcuts for the main window and popup
+
+use crate::config::settings::KeyBindings;
+use iced::event;
+use iced::keyboard::{key::Named, Key, Modifiers};
+use std::str::FromStr;
+
+/// Something a shortcut can trigger
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Check,
+    Enhance,
+    CopyResult,
+    Close,
+    NextIssue,
+    PreviousIssue,
+    /// Select the preset at this position in the picker
+    Preset(usize),
+}
+
+/// A key combination such as `ctrl+enter` or `shift+tab`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyCombo {
+    key: Key,
+    ctrl: bool,
+    shift: bool,
+    alt: bool,
+    logo: bool,
+}
+
+impl KeyCombo {
+    /// Whether a key press matches this combination exactly
+    pub fn matches(&self, key: &Key, modifiers: Modifiers) -> bool {
+        let same_key = match (&self.key, key) {
+            (Key::Character(a), Key::Character(b)) => a.eq_ignore_ascii_case(b),
+            (a, b) => a == b,
+        };
+
+        same_key
+            && self.ctrl == modifiers.control()
+            && self.shift == modifiers.shift()
+            && self.alt == modifiers.alt()
+            && self.logo == modifiers.logo()
+    }
+
+    /// Whether the combination uses Ctrl, Alt or Super
+    ///
+    /// Such combinations never produce text, so editors yield them to shortcuts.
+    pub fn has_command_modifier(&self) -> bool {
+        self.ctrl || self.alt || self.logo
+    }
+}
+
+impl FromStr for KeyCombo {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut combo = KeyCombo {
+            key: Key::Unidentified,
+            ctrl: false,
+            shift: false,
+            alt: false,
+            logo: false,
+        };
+
+        let parts: Vec<String> = s.split('+').map(|p| p.trim().to_lowercase()).collect();
+        let (key, modifiers) = parts
+            .split_last()
+            .filter(|(key, _)| !key.is_empty())
+            .ok_or_else(|| format!("empty key combination: {:?}", s))?;
+
+        for modifier in modifiers {
+            match modifier.as_str() {
+                "ctrl" | "control" => combo.ctrl = true,
+                "shift" => combo.shift = true,
+                "alt" => combo.alt = true,
+                "super" | "logo" | "meta" => combo.logo = true,
+                other => return Err(format!("unknown modifier {:?} in {:?}", other, s)),
+            }
+        }
+
+        combo.key = match key.as_str() {
+            "enter" | "return" => Key::Named(Named::Enter),
+            "esc" | "escape" => Key::Named(Named::Escape),
+            "tab" => Key::Named(Named::Tab),
+            "space" => Key::Named(Named::Space),
+            "backspace" => Key::Named(Named::Backspace),
+            "delete" => Key::Named(Named::Delete),
+            "up" => Key::Named(Named::ArrowUp),
+            "down" => Key::Named(Named::ArrowDown),
+            "left" => Key::Named(Named::ArrowLeft),
+            "right" => Key::Named(Named::ArrowRight),
+            "pageup" => Key::Named(Named::PageUp),
+            "pagedown" => Key::Named(Named::PageDown),
+            key if key.chars().count() == 1 => Key::Character(key.into()),
+            other => return Err(format!("unknown key {:?} in {:?}", other, s)),
+        };
+
+        Ok(combo)
+    }
+}
+
+/// Configured shortcuts, resolved from the `[keybindings]` config section
+#[derive(Debug, Clone, Default)]
+pub struct Shortcuts {
+    bindings: Vec<(KeyCombo, Action)>,
+}
+
+impl Shortcuts {
+    /// Parse the configured bindings, skipping (and logging) invalid ones
+    pub fn from_config(config: &KeyBindings) -> Self {
+        let mut entries = vec![
+            (config.check.as_str(), Action::Check),
+            (config.enhance.as_str(), Action::Enhance),
+            (config.copy_result.as_str(), Action::CopyResult),
+            (config.close.as_str(), Action::Close),
+            (config.next_issue.as_str(), Action::NextIssue),
+            (config.previous_issue.as_str(), Action::PreviousIssue),
+        ];
+        entries.extend(
+            config
+                .presets
+                .iter()
+                .enumerate()
+                .map(|(i, combo)| (combo.as_str(), Action::Preset(i))),
+        );
+
+        let bindings = entries
+            .into_iter()
+            .filter(|(combo, _)| !combo.trim().is_empty())
+            .filter_map(|(combo, action)| match combo.parse::<KeyCombo>() {
+                Ok(combo) => Some((combo, action)),
+                Err(e) => {
+                    tracing::warn!("Ignoring key binding for {:?}: {}", action, e);
+                    None
+                }
+            })
+            .collect();
+
+        Self { bindings }
+    }
+
+    /// Find the action bound to a key press that no widget handled
+    pub fn action(&self, key: &Key, modifiers: Modifiers, status: event::Status) -> Option<Action> {
+        if status == event::Status::Captured {
+            return None;
+        }
+        self.lookup(key, modifiers)
+    }
+
+    /// Whether an editor should leave a key press to the shortcuts
+    ///
+    /// Editors yield combinations with Ctrl/Alt/Super, and the close binding
+    /// while in the popup; plain keys keep typing into the editor.
+    pub fn reserves(&self, key: &Key, modifiers: Modifiers, is_popup: bool) -> bool {
+        self.bindings.iter().any(|(combo, action)| {
+            combo.matches(key, modifiers)
+                && (combo.has_command_modifier() || (is_popup && *action == Action::Close))
+        })
+    }
+
+    fn lookup(&self, key: &Key, modifiers: Modifiers) -> Option<Action> {
+        self.bindings
+            .iter()
+            .find(|(combo, _)| combo.matches(key, modifiers))
+            .map(|(_, action)| *action)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn combo(s: &str) -> KeyCombo {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn parses_modifiers_and_keys() {
+        let ctrl_enter = combo("ctrl+enter");
+        assert!(ctrl_enter.matches(&Key::Named(Named::Enter), Modifiers::CTRL));
+        assert!(!ctrl_enter.matches(&Key::Named(Named::Enter), Modifiers::empty()));
+        assert!(ctrl_enter.has_command_modifier());
+
+        // Case, spacing and aliases don't matter
+        assert_eq!(combo(" Control + Shift + C "), combo("ctrl+shift+c"));
+        assert_eq!(combo("super+return"), combo("logo+enter"));
+        assert_eq!(combo("esc"), combo("escape"));
+
+        let copy = combo("ctrl+shift+c");
+        assert!(copy.matches(
+            &Key::Character("C".into()),
+            Modifiers::CTRL | Modifiers::SHIFT
+        ));
+        assert!(!copy.matches(&Key::Character("c".into()), Modifiers::CTRL));
+
+        let preset = combo("1");
+        assert!(preset.matches(&Key::Character("1".into()), Modifiers::empty()));
+        assert!(!preset.has_command_modifier());
+    }
+
+    #[test]
+    fn rejects_invalid_combinations() {
+        for (input, error) in [
+            ("", "empty key combination"),
+            ("ctrl+", "empty key combination"),
+            ("hyper+a", "unknown modifier \"hyper\""),
+            ("ctrl+f13", "unknown key \"f13\""),
+            ("ctrl+ab", "unknown key \"ab\""),
+        ] {
+            let message = input.parse::<KeyCombo>().unwrap_err();
+            assert!(message.contains(error), "{:?}: {}", input, message);
+        }
+    }
+
+    #[test]
+    fn default_bindings_leave_ctrl_c_to_the_editors() {
+        let shortcuts = Shortcuts::from_config(&KeyBindings::default());
+        assert!(!shortcuts.reserves(&Key::Character("c".into()), Modifiers::CTRL, false));
+        assert_eq!(
+            shortcuts.lookup(
+                &Key::Character("c".into()),
+                Modifiers::CTRL | Modifiers::SHIFT
+            ),
+            Some(Action::CopyResult)
+        );
+
+        // Invalid bindings are skipped, the rest still work
+        let config = KeyBindings {
+            check: "ctrl+nope".to_string(),
+            ..KeyBindings::default()
+        };
+        let shortcuts = Shortcuts::from_config(&config);
+        assert_eq!(
+            shortcuts.lookup(&Key::Named(Named::Enter), Modifiers::CTRL),
+            None
+        );
+        assert_eq!(
+            shortcuts.lookup(&Key::Character("e".into()), Modifiers::CTRL),
+            Some(Action::Enhance)
+        );
+    }
+}
@@ -0,0 +1,118 @@
+//! Local HTTP stand-in for the Gemini API
+
+#![allow(dead_code)]
+
+use serde_json::{json, Value};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+/// A request received by the stand-in
+#[derive(Debug, Clone)]
+pub struct Received {
+    /// Path and query, e.g. `/model:generateContent?key=...`
+    pub path: String,
+    pub body: Value,
+}
+
+/// HTTP server answering each request with the next canned response
+pub struct StandIn {
+    pub base_url: String,
+    received: Arc<Mutex<Vec<Received>>>,
+}
+
+impl StandIn {
+    /// Start serving `responses` (status and body) in order; the last one repeats
+    pub async fn start(responses: Vec<(u16, String)>) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let mut responses: VecDeque<(u16, String)> = responses.into();
+
+        let log = received.clone();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let response = if responses.len() > 1 {
+                    responses.pop_front().unwrap()
+                } else {
+                    responses.front().cloned().unwrap()
+                };
+                if let Some(request) = serve(stream, response).await {
+                    log.lock().unwrap().push(request);
+                }
+            }
+        });
+
+        Self { base_url, received }
+    }
+
+    /// Requests received so far
+    pub fn received(&self) -> Vec<Received> {
+        self.received.lock().unwrap().clone()
+    }
+}
+
+/// Read one request and write the response
+async fn serve(mut stream: TcpStream, (status, body): (u16, String)) -> Option<Received> {
+    let mut data = Vec::new();
+    let mut buf = [0; 4096];
+    let header_end = loop {
+        let n = stream.read(&mut buf).await.ok()?;
+        if n == 0 {
+            return None;
+        }
+        data.extend_from_slice(&buf[..n]);
+        if let Some(pos) = data.windows(4).position(|w| w == b"\r\n\r\n") {
+            break pos + 4;
+        }
+    };
+
+    let head = String::from_utf8_lossy(&data[..header_end]).to_string();
+    let path = head.split_whitespace().nth(1)?.to_string();
+    let length = head
+        .lines()
+        .filter_map(|line| line.split_once(':'))
+        .find(|(name, _)| name.eq_ignore_ascii_case("content-length"))
+        .and_then(|(_, value)| value.trim().parse::<usize>().ok())
+        .unwrap_or(0);
+    while data.len() < header_end + length {
+        let n = stream.read(&mut buf).await.ok()?;
+        if n == 0 {
+            break;
+        }
+        data.extend_from_slice(&buf[..n]);
+    }
+    let request_body = serde_json::from_slice(&data[header_end..]).unwrap_or(Value::Null);
+
+    let head = format!(
+        "HTTP/1.1 {} Stand-in\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        status,
+        body.len()
+    );
+    stream.write_all(head.as_bytes()).await.ok()?;
+    stream.write_all(body.as_bytes()).await.ok()?;
+    stream.shutdown().await.ok()?;
+
+    Some(Received {
+        path,
+        body: request_body,
+    })
+}
+
+/// A successful Gemini response whose text is `text`
+pub fn gemini_response(text: &str) -> String {
+    json!({
+        "candidates": [{
+            "content": {"role": "model", "parts": [{"text": text}]},
+            "finishReason": "STOP"
+        }],
+        "usageMetadata": {"promptTokenCount": 120, "candidatesTokenCount": 30}
+    })
+    .to_string()
+}
+
+/// A Gemini response to a check
+pub fn check_response(corrected: &str, issues: Value) -> String {
+    gemini_response(&json!({"issues": issues, "corrected_text": corrected}).to_string())
+}
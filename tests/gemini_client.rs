@@ -0,0 +1,238 @@
+//! `GeminiClient` against a local stand-in for the Gemini API
+
+mod common;
+
+use akkurate::api::{
+    CheckResult, Fixtures, GeminiClient, GrammarIssue, KeyPool, MockProvider, ResultCache,
+};
+use akkurate::core::GrammarChecker;
+use common::{check_response, StandIn};
+use serde_json::json;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+const INPUT: &str = "She go to school every day.";
+const CORRECTED: &str = "She goes to school every day.";
+
+fn issues() -> serde_json::Value {
+    json!([{
+        "original": "go",
+        "corrected": "goes",
+        "explanation": "Third person singular",
+        "rule": "subject-verb agreement"
+    }])
+}
+
+/// A client sending requests to `server`, bypassing any system proxy
+fn client(server: &StandIn, keys: &[&str]) -> GeminiClient {
+    let keys = keys.iter().map(|key| key.to_string()).collect();
+    GeminiClient::new(String::new())
+        .with_keys(KeyPool::new(keys))
+        .with_http_client(reqwest::Client::builder().no_proxy().build().unwrap())
+        .with_endpoint(&server.base_url)
+        .with_model("test-model")
+}
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("akkurate-it-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+#[tokio::test]
+async fn check_sends_the_text_and_parses_the_answer() {
+    let server = StandIn::start(vec![(200, check_response(CORRECTED, issues()))]).await;
+
+    let result = client(&server, &["k1"])
+        .check_grammar(INPUT, "en")
+        .await
+        .unwrap();
+    assert_eq!(result.corrected_text, CORRECTED);
+    assert_eq!(result.issues.len(), 1);
+    assert_eq!(result.issues[0].corrected, "goes");
+
+    let received = server.received();
+    assert_eq!(received.len(), 1);
+    assert_eq!(received[0].path, "/test-model:generateContent?key=k1");
+    let body = &received[0].body;
+    assert!(body["systemInstruction"]["parts"][0]["text"].is_string());
+    let user_text = body["contents"][0]["parts"][1]["text"].as_str().unwrap();
+    assert!(user_text.contains(INPUT));
+}
+
+#[tokio::test]
+async fn api_errors_are_reported() {
+    let error = json!({"error": {"code": 400, "message": "API key not valid"}}).to_string();
+    let server = StandIn::start(vec![(400, error)]).await;
+
+    let error = client(&server, &["bad"])
+        .check_grammar(INPUT, "en")
+        .await
+        .unwrap_err();
+    let message = format!("{:#}", error);
+    assert!(message.contains("400"), "{}", message);
+    assert!(message.contains("API key not valid"), "{}", message);
+}
+
+#[tokio::test]
+async fn quota_errors_switch_to_the_next_key() {
+    let quota = json!({"error": {"code": 429, "status": "RESOURCE_EXHAUSTED"}}).to_string();
+    let server = StandIn::start(vec![
+        (429, quota),
+        (200, check_response(CORRECTED, issues())),
+    ])
+    .await;
+
+    let result = client(&server, &["k1", "k2"])
+        .check_grammar(INPUT, "en")
+        .await
+        .unwrap();
+    assert_eq!(result.corrected_text, CORRECTED);
+
+    let paths: Vec<_> = server.received().into_iter().map(|r| r.path).collect();
+    assert_eq!(
+        paths,
+        [
+            "/test-model:generateContent?key=k1",
+            "/test-model:generateContent?key=k2"
+        ]
+    );
+}
+
+#[tokio::test]
+async fn recorded_responses_are_replayed_without_requests() {
+    let dir = temp_dir("fixtures");
+    let server = StandIn::start(vec![(200, check_response(CORRECTED, issues()))]).await;
+
+    let recorded = client(&server, &["k1"])
+        .with_fixtures(Fixtures::record(&dir))
+        .check_grammar(INPUT, "en")
+        .await
+        .unwrap();
+    assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let file = std::fs::read_dir(&dir).unwrap().next().unwrap().unwrap();
+        let mode = file.metadata().unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+
+    // Nothing listens on the discard port, so any request would fail
+    let replay = GeminiClient::new("k1".to_string())
+        .with_endpoint("http://127.0.0.1:9")
+        .with_model("test-model")
+        .with_fixtures(Fixtures::replay(&dir));
+    let replayed = replay.check_grammar(INPUT, "en").await.unwrap();
+    assert_eq!(replayed.corrected_text, recorded.corrected_text);
+    assert_eq!(replayed.issues.len(), recorded.issues.len());
+
+    let missing = replay.check_grammar("Another text.", "en").await;
+    assert!(format!("{:#}", missing.unwrap_err()).contains("No recorded response"));
+
+    assert_eq!(server.received().len(), 1);
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[tokio::test]
+async fn mock_provider_answers_with_canned_results() {
+    let canned = CheckResult {
+        issues: vec![GrammarIssue {
+            original: "go".to_string(),
+            corrected: "goes".to_string(),
+            explanation: String::new(),
+            rule: String::new(),
+            offset: None,
+        }],
+        corrected_text: CORRECTED.to_string(),
+        summary: None,
+        unchecked: Vec::new(),
+    };
+    let mock = Arc::new(MockProvider::new().with_check_result(INPUT, canned));
+    let client = GeminiClient::new(String::new()).with_mock(mock.clone());
+
+    let result = client.check_grammar(INPUT, "en").await.unwrap();
+    assert_eq!(result.corrected_text, CORRECTED);
+
+    let unknown = client.check_grammar("Fine as it is.", "en").await.unwrap();
+    assert_eq!(unknown.corrected_text, "Fine as it is.");
+    assert!(unknown.issues.is_empty());
+    assert_eq!(mock.calls(), 2);
+}
+
+#[tokio::test]
+async fn long_texts_are_checked_in_parts_and_merged() {
+    let first = "She go to school every day.";
+    let second = "He have a dog.";
+    let text = format!("{}\n\n{}", first, second);
+    let result_for = |original: &str, corrected: &str, fixed: &str| CheckResult {
+        issues: vec![GrammarIssue {
+            original: original.to_string(),
+            corrected: corrected.to_string(),
+            explanation: String::new(),
+            rule: String::new(),
+            offset: None,
+        }],
+        corrected_text: fixed.to_string(),
+        summary: None,
+        unchecked: Vec::new(),
+    };
+    let mock = Arc::new(
+        MockProvider::new()
+            .with_check_result(first, result_for("go", "goes", CORRECTED))
+            .with_check_result(second, result_for("have", "has", "He has a dog.")),
+    );
+    let checker = GrammarChecker::new(GeminiClient::new(String::new()).with_mock(mock.clone()))
+        .with_limits(8, 2);
+
+    let result = checker.check_grammar(&text, "en").await.unwrap();
+    assert_eq!(mock.calls(), 2);
+    assert_eq!(
+        result.corrected_text,
+        format!("{}\n\n{}", CORRECTED, "He has a dog.")
+    );
+    let offsets: Vec<_> = result.issues.iter().map(|issue| issue.offset).collect();
+    assert_eq!(offsets, [Some(4), Some(first.len() + 2 + 3)]);
+}
+
+#[tokio::test]
+async fn parts_that_keep_failing_are_left_unchecked() {
+    let text = format!("{}\n\n{}", INPUT, INPUT);
+    let error = json!({"error": {"code": 500, "message": "Internal error"}}).to_string();
+    // Whichever part asks first is corrected; the other fails twice
+    let server = StandIn::start(vec![
+        (200, check_response(CORRECTED, issues())),
+        (500, error),
+    ])
+    .await;
+    let checker = GrammarChecker::new(client(&server, &["k1"])).with_limits(8, 2);
+
+    let result = checker.check_grammar(&text, "en").await.unwrap();
+    assert_eq!(server.received().len(), 3);
+    assert_eq!(result.corrected_text, format!("{}\n\n{}", CORRECTED, INPUT));
+    assert_eq!(result.unchecked.len(), 1);
+    assert!(
+        result.unchecked[0].contains("of 2"),
+        "{}",
+        result.unchecked[0]
+    );
+}
+
+#[tokio::test]
+async fn cached_results_skip_the_request() {
+    let dir = temp_dir("cache");
+    let server = StandIn::start(vec![(200, check_response(CORRECTED, issues()))]).await;
+    let client = client(&server, &["k1"]).with_cache(ResultCache::new(
+        dir.clone(),
+        Duration::from_secs(3600),
+        1024 * 1024,
+    ));
+
+    let first = client.check_grammar(INPUT, "en").await.unwrap();
+    let second = client.check_grammar(INPUT, "en").await.unwrap();
+    assert_eq!(first.corrected_text, second.corrected_text);
+    assert_eq!(server.received().len(), 1);
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
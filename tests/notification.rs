@@ -0,0 +1,176 @@
+//! Result notifications against a stub daemon on a private session bus
+//!
+//! Skipped when `dbus-daemon` isn't installed.
+
+#![cfg(target_os = "linux")]
+
+use akkurate::api::{CheckResult, GrammarIssue};
+use akkurate::ui::notification::{notify_check_result, NotificationAction};
+use akkurate::ui::Language;
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader};
+use std::process::{Child, Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use zbus::zvariant::OwnedValue;
+
+const PATH: &str = "/org/freedesktop/Notifications";
+const INTERFACE: &str = "org.freedesktop.Notifications";
+
+/// A `dbus-daemon` running for the duration of the test
+struct SessionBus {
+    daemon: Child,
+    address: String,
+}
+
+impl SessionBus {
+    fn start() -> Option<Self> {
+        let mut daemon = Command::new("dbus-daemon")
+            .args(["--session", "--nofork", "--print-address=1"])
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .ok()?;
+        let mut address = String::new();
+        BufReader::new(daemon.stdout.take()?)
+            .read_line(&mut address)
+            .ok()?;
+        Some(Self {
+            daemon,
+            address: address.trim().to_string(),
+        })
+    }
+}
+
+impl Drop for SessionBus {
+    fn drop(&mut self) {
+        let _ = self.daemon.kill();
+        let _ = self.daemon.wait();
+    }
+}
+
+/// Notification summary, body and actions the stub received
+type Shown = Arc<Mutex<Vec<(String, String, Vec<String>)>>>;
+
+struct StubDaemon {
+    shown: Shown,
+}
+
+#[zbus::interface(name = "org.freedesktop.Notifications")]
+impl StubDaemon {
+    #[allow(clippy::too_many_arguments)]
+    fn notify(
+        &self,
+        _app_name: String,
+        _replaces_id: u32,
+        _app_icon: String,
+        summary: String,
+        body: String,
+        actions: Vec<String>,
+        _hints: HashMap<String, OwnedValue>,
+        _expire_timeout: i32,
+    ) -> u32 {
+        let mut shown = self.shown.lock().unwrap();
+        shown.push((summary, body, actions));
+        shown.len() as u32
+    }
+
+    fn get_capabilities(&self) -> Vec<String> {
+        vec!["actions".to_string(), "body".to_string()]
+    }
+
+    fn get_server_information(&self) -> (String, String, String, String) {
+        let name = "stub".to_string();
+        (name.clone(), name.clone(), name, "1.2".to_string())
+    }
+
+    fn close_notification(&self, _id: u32) {}
+}
+
+/// Keep sending a signal for notification `id` until the client has reacted
+///
+/// The client subscribes only after the notification is shown, so a single
+/// signal could arrive before it listens.
+fn keep_signalling(
+    daemon: zbus::blocking::Connection,
+    shown: Shown,
+    id: u32,
+    member: &'static str,
+    action: &'static str,
+) -> Arc<AtomicBool> {
+    let done = Arc::new(AtomicBool::new(false));
+    let stop = done.clone();
+    std::thread::spawn(move || {
+        while !stop.load(Ordering::Relaxed) {
+            if shown.lock().unwrap().len() as u32 >= id {
+                let _ = match member {
+                    "ActionInvoked" => {
+                        daemon.emit_signal(None::<&str>, PATH, INTERFACE, member, &(id, action))
+                    }
+                    _ => daemon.emit_signal(None::<&str>, PATH, INTERFACE, member, &(id, 2u32)),
+                };
+            }
+            std::thread::sleep(Duration::from_millis(50));
+        }
+    });
+    done
+}
+
+#[test]
+fn actions_are_reported_from_the_notification_daemon() {
+    let Some(bus) = SessionBus::start() else {
+        eprintln!("dbus-daemon not available, skipping");
+        return;
+    };
+    // This test binary has no other tests, so nothing else reads the variable
+    std::env::set_var("DBUS_SESSION_BUS_ADDRESS", &bus.address);
+
+    let shown = Shown::default();
+    let daemon = zbus::blocking::connection::Builder::address(bus.address.as_str())
+        .unwrap()
+        .name(INTERFACE)
+        .unwrap()
+        .serve_at(
+            PATH,
+            StubDaemon {
+                shown: shown.clone(),
+            },
+        )
+        .unwrap()
+        .build()
+        .unwrap();
+
+    let result = CheckResult {
+        issues: vec![GrammarIssue {
+            original: "go".to_string(),
+            corrected: "goes".to_string(),
+            explanation: String::new(),
+            rule: String::new(),
+            offset: Some(4),
+        }],
+        corrected_text: "She goes to school.".to_string(),
+        summary: None,
+        unchecked: Vec::new(),
+    };
+
+    let cases = [
+        ("ActionInvoked", "undo", NotificationAction::UndoCopy),
+        ("ActionInvoked", "details", NotificationAction::OpenDetails),
+        ("ActionInvoked", "default", NotificationAction::OpenDetails),
+        ("NotificationClosed", "", NotificationAction::Dismissed),
+    ];
+    for (id, (member, action, expected)) in (1..).zip(cases) {
+        let done = keep_signalling(daemon.clone(), shown.clone(), id, member, action);
+        let got = notify_check_result(&result, Language::English).unwrap();
+        done.store(true, Ordering::Relaxed);
+        assert_eq!(got, expected, "{} {:?}", member, action);
+    }
+
+    let shown = shown.lock().unwrap();
+    let (summary, body, actions) = &shown[0];
+    assert!(summary.contains('1'), "{}", summary);
+    assert!(body.contains("She goes to school."), "{}", body);
+    assert!(actions.contains(&"undo".to_string()));
+    assert!(actions.contains(&"details".to_string()));
+}